@@ -2,11 +2,6 @@ use aws_gamelift_server_sdk_rs::{
     log_parameters::LogParameters, process_parameters::ProcessParameters,
 };
 
-static CLIENT: once_cell::sync::Lazy<tokio::sync::Mutex<aws_gamelift_server_sdk_rs::api::Api>> =
-    once_cell::sync::Lazy::new(|| {
-        tokio::sync::Mutex::new(aws_gamelift_server_sdk_rs::api::Api::default())
-    });
-
 #[tokio::main]
 async fn main() {
     env_logger::init();
@@ -16,24 +11,21 @@ async fn main() {
         aws_gamelift_server_sdk_rs::api::Api::get_sdk_version()
     );
 
-    if let Err(error) = CLIENT.lock().await.init_sdk().await {
+    let api = aws_gamelift_server_sdk_rs::api::Api::default();
+
+    if let Err(error) = api.init_sdk().await {
         log::error!("{:?}", error);
     }
 
-    if let Err(error) = CLIENT
-        .lock()
-        .await
+    let callback_api = api.clone();
+    if let Err(error) = api
         .process_ready(ProcessParameters {
             on_start_game_session: Box::new(move |game_session| {
+                let api = callback_api.clone();
                 Box::pin(async move {
                     log::debug!("{:?}", game_session);
 
-                    CLIENT
-                        .lock()
-                        .await
-                        .activate_game_session()
-                        .await
-                        .expect("Cannot activate game session");
+                    api.activate_game_session().await.expect("Cannot activate game session");
 
                     log::info!("Session active!");
                 })
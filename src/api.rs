@@ -7,21 +7,44 @@ use crate::{
 
 pub const SDK_VERSION: &str = "4.0.2";
 
-#[derive(Default)]
+/// A handle to the SDK. It is cheap to clone, and all clones share the same
+/// state, so callbacks can capture a clone and call back into the SDK.
+#[derive(Clone)]
 pub struct Api {
-    state: crate::server_state::ServerState,
+    state: std::sync::Arc<crate::server_state::ServerState>,
+}
+
+impl Default for Api {
+    /// Creates the SDK with
+    /// [SdkConfig::from_env](crate::sdk_config::SdkConfig::from_env).
+    fn default() -> Self {
+        Self::new(crate::sdk_config::SdkConfig::from_env())
+    }
 }
 
 impl Api {
+    /// Creates the SDK that connects to AuxProxy using the given
+    /// configuration.
+    pub fn new(config: crate::sdk_config::SdkConfig) -> Self {
+        Self { state: std::sync::Arc::new(crate::server_state::ServerState::new(config)) }
+    }
+
     /// Returns the current version number of the SDK built into the server
     /// process.
     pub fn get_sdk_version() -> &'static str {
         SDK_VERSION
     }
 
+    /// Returns the version of the Server SDK whose protocol is spoken, which
+    /// depends on the [protocol](crate::sdk_config::SdkConfig::protocol):
+    /// [SDK_VERSION] for AuxProxy, a 5.x version for the WebSocket protocol.
+    pub fn protocol_sdk_version(&self) -> &'static str {
+        self.state.sdk_version()
+    }
+
     /// Initializes the GameLift SDK. This method should be called on launch,
     /// before any other GameLift-related initialization occurs.
-    pub async fn init_sdk(&mut self) -> Result<(), GameLiftErrorType> {
+    pub async fn init_sdk(&self) -> Result<(), GameLiftErrorType> {
         self.state.initialize_networking().await
     }
 
@@ -31,18 +54,31 @@ impl Api {
     /// that are required before the server process can host a game session.
     /// This method should be called only once per process.
     pub async fn process_ready(
-        &mut self,
+        &self,
         process_parameters: crate::process_parameters::ProcessParameters,
     ) -> Result<(), GameLiftErrorType> {
         self.state.process_ready(process_parameters).await
     }
 
+    /// Same as [process_ready](crate::api::Api::process_ready), but instead
+    /// of invoking callbacks delivers GameLift events as a stream of
+    /// [SdkEvent](crate::sdk_event::SdkEvent)s, e.g. to handle them in the
+    /// game loop with `tokio::select!`. Events are delivered in order, and the
+    /// SDK waits while the stream is not being read.
+    pub async fn process_ready_with_events(
+        &self,
+        port: i32,
+        log_parameters: crate::log_parameters::LogParameters,
+    ) -> Result<crate::sdk_event::SdkEvents, GameLiftErrorType> {
+        self.state.process_ready_with_events(port, log_parameters).await
+    }
+
     /// Notifies the GameLift service that the server process is shutting down.
     /// This method should be called after all other cleanup tasks, including
     /// shutting down all active game sessions. This method should exit with an
     /// exit code of 0; a non-zero exit code results in an event message that
     /// the process did not exit cleanly.
-    pub async fn process_ending(&mut self) -> Result<(), GameLiftErrorType> {
+    pub async fn process_ending(&self) -> Result<(), GameLiftErrorType> {
         self.state.process_ending().await
     }
 
@@ -51,7 +87,7 @@ impl Api {
     /// should be called as part of the on_start_game_session() callback
     /// function, after all game session initialization has been completed.
     pub async fn activate_game_session(&self) -> Result<(), GameLiftErrorType> {
-        self.state.activate_game_session().await
+        self.state.activate_game_session(None).await
     }
 
     /// Notifies the GameLift service that the server process has ended the
@@ -65,13 +101,17 @@ impl Api {
     /// the game session stops. Instead, call
     /// [process_ending](crate::api::Api::process_ending) to signal that
     /// both the game session and the server process are ending.
+    ///
+    /// Fails with
+    /// [UnsupportedOperation](crate::error::GameLiftErrorType::UnsupportedOperation)
+    /// with the [WebSocket](crate::sdk_config::Protocol::WebSocket) protocol.
     #[deprecated(
         since = "4.0.1",
         note = "Instead, the server process should call process_ending() after a game session has \
                 ended"
     )]
     pub async fn terminate_game_session(&self) -> Result<(), GameLiftErrorType> {
-        self.state.terminate_game_session().await
+        self.state.terminate_game_session(None).await
     }
 
     /// Updates the current game session's ability to accept new player
@@ -82,17 +122,46 @@ impl Api {
         &self,
         player_session_policy: crate::entity::PlayerSessionCreationPolicy,
     ) -> Result<(), GameLiftErrorType> {
-        self.state.update_player_session_creation_policy(player_session_policy).await
+        self.state.update_player_session_creation_policy(None, player_session_policy).await
     }
 
     /// Retrieves the ID of the game session currently being hosted by the
     /// server process, if the server process is active.
+    /// Fails with
+    /// [AmbiguousGameSession](crate::error::GameLiftErrorType::AmbiguousGameSession)
+    /// if the process hosts several game sessions, like the other methods
+    /// targeting the current game session.
     pub async fn get_game_session_id(
         &self,
     ) -> Result<crate::entity::GameSessionId, crate::error::GameLiftErrorType> {
         self.state.get_game_session_id().await
     }
 
+    /// Returns a handle to the game session with the given ID, if the process
+    /// hosts it.
+    pub fn game_session(
+        &self,
+        game_session_id: &str,
+    ) -> Option<crate::game_session::GameSessionHandle> {
+        self.state.game_session_state(game_session_id).map(|_| {
+            crate::game_session::GameSessionHandle::new(
+                self.state.clone(),
+                game_session_id.to_string(),
+            )
+        })
+    }
+
+    /// Returns handles to all game sessions the process hosts.
+    pub fn game_sessions(&self) -> Vec<crate::game_session::GameSessionHandle> {
+        self.state
+            .game_session_ids()
+            .into_iter()
+            .map(|game_session_id| {
+                crate::game_session::GameSessionHandle::new(self.state.clone(), game_session_id)
+            })
+            .collect()
+    }
+
     /// Returns the time that a server process is scheduled to be shut down, if
     /// a termination time is available. A server process takes this action
     /// after receiving an on_process_terminate() callback from the GameLift
@@ -112,17 +181,77 @@ impl Api {
         self.state.get_termination_time().await
     }
 
+    /// Same as [get_termination_time](crate::api::Api::get_termination_time),
+    /// but converts the termination time for timers and countdowns.
+    pub async fn termination_countdown(
+        &self,
+    ) -> Result<crate::termination::TerminationCountdown, crate::error::GameLiftErrorType> {
+        self.get_termination_time()
+            .await
+            .map(crate::termination::TerminationCountdown::from_termination_time)
+    }
+
+    /// Invokes `callback` at the given offsets before the termination time,
+    /// e.g. to warn players 120, 30 and 5 seconds before the process is shut
+    /// down. The countdown starts when GameLift sends the termination time.
+    /// See [TerminationCountdown::schedule](crate::termination::TerminationCountdown::schedule).
+    pub fn on_termination_countdown(
+        &self,
+        offsets: Vec<std::time::Duration>,
+        callback: Box<crate::termination::OnCountdownType>,
+    ) -> crate::termination::CountdownHandle {
+        crate::termination::CountdownHandle::spawn(self.clone(), offsets, callback)
+    }
+
     /// Notifies the GameLift service that a player with the specified player
     /// session ID has connected to the server process and needs validation.
     /// GameLift verifies that the player session ID is valid—that is, that the
     /// player ID has reserved a player slot in the game session. Once
     /// validated, GameLift changes the status of the player slot from RESERVED
     /// to ACTIVE.
+    ///
+    /// The SDK keeps track of the player sessions and fails with
+    /// [PlayerSessionAlreadyAccepted](crate::error::GameLiftErrorType::PlayerSessionAlreadyAccepted)
+    /// without calling GameLift if the player session has been accepted
+    /// before. Player sessions accepted but never removed when the game
+    /// session ends are reported as
+    /// [PlayerSessionsLeaked](crate::notification::Notification::PlayerSessionsLeaked).
     pub async fn accept_player_session(
         &self,
         player_session_id: crate::entity::PlayerSessionId,
     ) -> Result<(), GameLiftErrorType> {
-        self.state.accept_player_session(player_session_id).await
+        self.state.accept_player_session(None, player_session_id).await
+    }
+
+    /// Returns a [Gatekeeper](crate::gatekeeper::Gatekeeper) that admits
+    /// players into the current game session.
+    pub fn gatekeeper<S>(
+        &self,
+        config: crate::gatekeeper::GatekeeperConfig,
+        handshake: Box<crate::gatekeeper::HandshakeType<S>>,
+    ) -> crate::gatekeeper::Gatekeeper<S> {
+        crate::gatekeeper::Gatekeeper::new(self.state.clone(), None, config, handshake)
+    }
+
+    /// Returns the number of connected players in all game sessions, i.e. of
+    /// player sessions that have been accepted but not removed yet.
+    pub fn player_count(&self) -> usize {
+        self.state.player_count(None)
+    }
+
+    /// Marks the process as draining: it stops accepting new players for
+    /// good, e.g. because it is about to shut down. A running
+    /// [CapacityPolicy](crate::capacity_policy::CapacityPolicy) denies new
+    /// players from then on. The
+    /// [ShutdownCoordinator](crate::shutdown::ShutdownCoordinator) calls this
+    /// when it starts shutting down.
+    pub fn start_draining(&self) {
+        self.state.start_draining()
+    }
+
+    /// Whether [start_draining](Self::start_draining) has been called.
+    pub fn is_draining(&self) -> bool {
+        self.state.is_draining()
     }
 
     /// Notifies the GameLift service that a player with the specified player
@@ -133,7 +262,7 @@ impl Api {
         &self,
         player_session_id: crate::entity::PlayerSessionId,
     ) -> Result<(), GameLiftErrorType> {
-        self.state.remove_player_session(player_session_id).await
+        self.state.remove_player_session(None, player_session_id).await
     }
 
     /// Retrieves player session data, including settings, session metadata, and
@@ -147,6 +276,57 @@ impl Api {
         self.state.describe_player_sessions(describe_player_sessions_request).await
     }
 
+    /// Same as
+    /// [describe_player_sessions](crate::api::Api::describe_player_sessions),
+    /// but returns the player sessions one by one and requests the next page
+    /// with `next_token` as needed. `limit` is the page size and defaults to
+    /// [DEFAULT_PAGE_SIZE](crate::player_session::DEFAULT_PAGE_SIZE). The
+    /// stream ends after the first error.
+    pub fn describe_player_sessions_stream(
+        &self,
+        describe_player_sessions_request: crate::entity::DescribePlayerSessionsRequest,
+    ) -> impl futures_util::Stream<Item = Result<crate::entity::PlayerSession, GameLiftErrorType>>
+           + Send
+           + 'static {
+        crate::player_session::describe_stream(self.state.clone(), describe_player_sessions_request)
+    }
+
+    /// Streams all player sessions of the current game session.
+    pub async fn current_game_session_player_sessions(
+        &self,
+    ) -> Result<
+        impl futures_util::Stream<Item = Result<crate::entity::PlayerSession, GameLiftErrorType>>
+            + Send
+            + 'static,
+        GameLiftErrorType,
+    > {
+        let game_session_id = self.get_game_session_id().await?;
+        Ok(self.describe_player_sessions_stream(crate::entity::DescribePlayerSessionsRequest {
+            game_session_id: Some(game_session_id),
+            ..Default::default()
+        }))
+    }
+
+    /// Streams the ACTIVE player sessions of the current game session, i.e.
+    /// of the connected players.
+    pub async fn active_player_sessions(
+        &self,
+    ) -> Result<
+        impl futures_util::Stream<Item = Result<crate::entity::PlayerSession, GameLiftErrorType>>
+            + Send
+            + 'static,
+        GameLiftErrorType,
+    > {
+        let game_session_id = self.get_game_session_id().await?;
+        Ok(self.describe_player_sessions_stream(crate::entity::DescribePlayerSessionsRequest {
+            game_session_id: Some(game_session_id),
+            player_session_status_filter: Some(
+                crate::entity::PlayerSessionStatus::Active.to_string(),
+            ),
+            ..Default::default()
+        }))
+    }
+
     /// Sends a request to find new players for open slots in a game session
     /// created with FlexMatch. See also the AWS SDK action
     /// [start_match_backfill](crate::api::Api::start_match_backfill). With this
@@ -161,7 +341,8 @@ impl Api {
     /// A server process can have only one active match backfill request at a
     /// time. To send a new request, first call
     /// [stop_match_backfill](crate::api::Api::stop_match_backfill) to cancel
-    /// the original request.
+    /// the original request. [backfill](crate::api::Api::backfill) takes care
+    /// of this and builds the request from the matchmaker data.
     pub async fn start_match_backfill(
         &self,
         request: crate::entity::StartMatchBackfillRequest,
@@ -169,6 +350,37 @@ impl Api {
         self.state.backfill_matchmaking(request).await
     }
 
+    /// Returns the match backfill manager of the current game session, which
+    /// builds backfill requests and tracks their tickets.
+    pub async fn backfill(&self) -> Result<crate::backfill::BackfillManager, GameLiftErrorType> {
+        let game_session_id = self.get_game_session_id().await?;
+        Ok(crate::backfill::BackfillManager::new(self.state.clone(), game_session_id))
+    }
+
+    /// Runs a [CapacityPolicy](crate::capacity_policy::CapacityPolicy) for
+    /// the current game session.
+    pub async fn run_capacity_policy(
+        &self,
+        policy: crate::capacity_policy::CapacityPolicy,
+    ) -> Result<crate::capacity_policy::CapacityPolicyHandle, GameLiftErrorType> {
+        let game_session_id = self.get_game_session_id().await?;
+        Ok(crate::capacity_policy::CapacityPolicyHandle::spawn(
+            self.state.clone(),
+            game_session_id,
+            policy,
+        ))
+    }
+
+    /// Runs an [IdlePolicy](crate::idle_policy::IdlePolicy) for the current
+    /// game session.
+    pub async fn run_idle_policy(
+        &self,
+        policy: crate::idle_policy::IdlePolicy,
+    ) -> Result<crate::idle_policy::IdlePolicyHandle, GameLiftErrorType> {
+        let game_session_id = self.get_game_session_id().await?;
+        Ok(crate::idle_policy::IdlePolicyHandle::spawn(self.state.clone(), game_session_id, policy))
+    }
+
     /// Cancels an active match backfill request that was created with
     /// [start_match_backfill](crate::api::Api::start_match_backfill). See also
     /// the AWS SDK action StopMatchmaking(). Learn more about the FlexMatch
@@ -185,12 +397,75 @@ impl Api {
     /// generated when a new fleet is created with the certificate configuration
     /// set to GENERATED. Use this certificate to establish a secure connection
     /// with a game client and to encrypt client/server communication.
+    ///
+    /// With the [WebSocket](crate::sdk_config::Protocol::WebSocket) protocol
+    /// GameLift only reports the certificate path and the hostname, so the
+    /// other paths are empty.
     pub async fn get_instance_certificate(
         &self,
     ) -> Result<GetInstanceCertificateResult, GameLiftErrorType> {
         self.state.get_instance_certificate().await
     }
 
+    /// Loads the instance certificate into a TLS configuration, see
+    /// [InstanceTls](crate::tls::InstanceTls).
+    #[cfg(feature = "tls")]
+    pub async fn instance_tls(&self) -> Result<crate::tls::InstanceTls, GameLiftErrorType> {
+        let certificate = self.get_instance_certificate().await?;
+        crate::tls::InstanceTls::load(&certificate)
+    }
+
+    /// Reloads `tls` whenever the certificate files change, and asks GameLift
+    /// for a new certificate shortly before it expires. See
+    /// [CertificateWatcherConfig](crate::tls::CertificateWatcherConfig).
+    #[cfg(feature = "tls")]
+    pub fn watch_instance_tls(
+        &self,
+        tls: &crate::tls::InstanceTls,
+        config: crate::tls::CertificateWatcherConfig,
+    ) -> crate::tls::CertificateWatcherHandle {
+        crate::tls::CertificateWatcherHandle::spawn(self.clone(), tls.clone(), config)
+    }
+
+    /// Returns the lifecycle state of the server process and its game
+    /// session. Changes are also sent as
+    /// [StateChanged](crate::notification::Notification::StateChanged)
+    /// notifications.
+    pub fn state(&self) -> crate::server_state::ProcessState {
+        self.state.state()
+    }
+
+    /// Returns the registry of health checks that decide, together with
+    /// `on_health_check`, whether the process is reported healthy.
+    pub fn health_registry(&self) -> crate::health::HealthRegistry {
+        self.state.health_registry()
+    }
+
+    /// Creates a [Watchdog](crate::watchdog::Watchdog) for a game loop and
+    /// registers it in the [health registry](crate::api::Api::health_registry)
+    /// under `name`.
+    pub fn watchdog(
+        &self,
+        name: impl Into<String>,
+        config: crate::watchdog::WatchdogConfig,
+    ) -> crate::watchdog::Watchdog {
+        crate::watchdog::Watchdog::register(self.health_registry(), name.into(), config)
+    }
+
+    /// Returns the detailed result of the last health report, if any.
+    pub fn health_snapshot(&self) -> Option<crate::health::HealthSnapshot> {
+        self.state.health_registry().snapshot()
+    }
+
+    /// Subscribes to [notifications](crate::notification::Notification) about
+    /// SDK-internal events, e.g. losing and restoring the connection to
+    /// AuxProxy. Only notifications sent after this call are received.
+    pub fn subscribe_notifications(
+        &self,
+    ) -> tokio::sync::broadcast::Receiver<crate::notification::Notification> {
+        self.state.subscribe_notifications()
+    }
+
     pub async fn destroy(&self) -> bool {
         self.state.shutdown().await
     }
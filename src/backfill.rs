@@ -0,0 +1,314 @@
+/// The state of a match backfill ticket started with a [BackfillManager].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+pub enum BackfillTicketState {
+    /// The request has been sent, and GameLift is looking for players.
+    Pending,
+
+    /// GameLift has found players and delivered the updated matchmaker data.
+    Fulfilled,
+
+    /// The request failed, e.g. because of an invalid player list.
+    Failed,
+
+    /// GameLift did not find players within the matchmaker's time limit.
+    TimedOut,
+
+    /// The request has been stopped.
+    Cancelled,
+}
+
+impl BackfillTicketState {
+    /// Returns the state a ticket moves to after an `UpdateGameSession` event
+    /// for it.
+    pub(crate) fn from_update_reason(update_reason: crate::entity::UpdateReason) -> Option<Self> {
+        match update_reason {
+            crate::entity::UpdateReason::MatchmakingDataUpdated => Some(Self::Fulfilled),
+            crate::entity::UpdateReason::BackfillFailed => Some(Self::Failed),
+            crate::entity::UpdateReason::BackfillTimedOut => Some(Self::TimedOut),
+            crate::entity::UpdateReason::BackfillCancelled => Some(Self::Cancelled),
+            crate::entity::UpdateReason::Unknown => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackfillTicket {
+    pub ticket_id: crate::entity::TicketId,
+    pub state: BackfillTicketState,
+    pub started_at: std::time::SystemTime,
+
+    /// When the ticket left the [Pending](BackfillTicketState::Pending) state.
+    pub finished_at: Option<std::time::SystemTime>,
+}
+
+/// Backfill tickets of a single game session. At most one of them is
+/// pending.
+#[derive(Default)]
+pub(crate) struct BackfillTickets {
+    tickets: Vec<BackfillTicket>,
+}
+
+impl BackfillTickets {
+    /// Records a new pending ticket. Fails if another one is pending.
+    pub fn start(
+        &mut self,
+        ticket_id: crate::entity::TicketId,
+    ) -> Result<(), crate::error::GameLiftErrorType> {
+        if let Some(active) = self.active() {
+            return Err(crate::error::GameLiftErrorType::BackfillAlreadyActive(active.ticket_id));
+        }
+        self.tickets.push(BackfillTicket {
+            ticket_id,
+            state: BackfillTicketState::Pending,
+            started_at: std::time::SystemTime::now(),
+            finished_at: None,
+        });
+        Ok(())
+    }
+
+    /// Forgets a ticket GameLift has not accepted.
+    pub fn discard(&mut self, ticket_id: &str) {
+        self.tickets.retain(|ticket| ticket.ticket_id != ticket_id);
+    }
+
+    /// Moves a pending ticket to its final state. Returns `false` if the
+    /// ticket is unknown or has finished before.
+    pub fn finish(&mut self, ticket_id: &str, state: BackfillTicketState) -> bool {
+        match self.tickets.iter_mut().find(|ticket| ticket.ticket_id == ticket_id) {
+            Some(ticket) if ticket.state == BackfillTicketState::Pending => {
+                ticket.state = state;
+                ticket.finished_at = Some(std::time::SystemTime::now());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn active(&self) -> Option<BackfillTicket> {
+        self.tickets.iter().find(|ticket| ticket.state == BackfillTicketState::Pending).cloned()
+    }
+
+    pub fn get(&self, ticket_id: &str) -> Option<BackfillTicket> {
+        self.tickets.iter().find(|ticket| ticket.ticket_id == ticket_id).cloned()
+    }
+
+    /// Returns all tickets, oldest first.
+    pub fn tickets(&self) -> Vec<BackfillTicket> {
+        self.tickets.clone()
+    }
+}
+
+/// Builds the players of a backfill request: everybody in the latest
+/// matchmaker data except the players whose player sessions have been
+/// removed. Removed player sessions must have been described before, so that
+/// their player IDs are known.
+pub(crate) fn backfill_players(
+    matchmaker_data: &crate::entity::MatchmakerData,
+    player_sessions: &crate::player_session::PlayerSessionRegistry,
+) -> Vec<crate::entity::Player> {
+    let mut removed_player_ids = std::collections::HashSet::new();
+    for record in player_sessions.records() {
+        if record.state != crate::player_session::PlayerSessionState::Removed {
+            continue;
+        }
+        match record.player_id {
+            Some(player_id) => {
+                removed_player_ids.insert(player_id);
+            }
+            None => log::warn!(
+                "The player of the removed player session {} is unknown and may be backfilled as \
+                 a current player.",
+                record.player_session_id
+            ),
+        }
+    }
+    matchmaker_data
+        .players()
+        .into_iter()
+        .filter(|player| {
+            player
+                .player_id
+                .as_ref()
+                .is_some_and(|player_id| !removed_player_ids.contains(player_id))
+        })
+        .collect()
+}
+
+/// Generates a ticket ID, so that the ticket can be tracked before GameLift
+/// answers.
+pub(crate) fn new_ticket_id() -> crate::entity::TicketId {
+    static COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    format!(
+        "backfill-{}-{}-{}",
+        std::process::id(),
+        timestamp,
+        COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+    )
+}
+
+/// Runs FlexMatch match backfill for a game session. Get it with
+/// [GameSessionHandle::backfill](crate::game_session::GameSessionHandle::backfill)
+/// or [Api::backfill](crate::api::Api::backfill).
+///
+/// The manager builds requests from the latest matchmaker data of the game
+/// session and the player sessions known to the process, allows only one
+/// pending ticket at a time, and follows the tickets through the
+/// `UpdateGameSession` events GameLift sends for them. Ticket changes are also
+/// sent as
+/// [BackfillTicketUpdated](crate::notification::Notification::BackfillTicketUpdated)
+/// notifications. To start and stop backfill automatically, run a
+/// [BackfillPolicy](crate::backfill_policy::BackfillPolicy).
+#[derive(Clone)]
+pub struct BackfillManager {
+    state: std::sync::Arc<crate::server_state::ServerState>,
+    game_session_id: crate::entity::GameSessionId,
+}
+
+impl std::fmt::Debug for BackfillManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BackfillManager").field("game_session_id", &self.game_session_id).finish()
+    }
+}
+
+impl BackfillManager {
+    pub(crate) fn new(
+        state: std::sync::Arc<crate::server_state::ServerState>,
+        game_session_id: crate::entity::GameSessionId,
+    ) -> Self {
+        Self { state, game_session_id }
+    }
+
+    pub fn game_session_id(&self) -> &crate::entity::GameSessionId {
+        &self.game_session_id
+    }
+
+    /// Sends a match backfill request for the game session and returns its
+    /// ticket. Fails with
+    /// [BackfillAlreadyActive](crate::error::GameLiftErrorType::BackfillAlreadyActive)
+    /// if a ticket is pending, and with
+    /// [MatchmakerDataNotSet](crate::error::GameLiftErrorType::MatchmakerDataNotSet)
+    /// if the game session was not created by FlexMatch.
+    pub async fn start(&self) -> Result<BackfillTicket, crate::error::GameLiftErrorType> {
+        self.state.start_backfill(&self.game_session_id).await
+    }
+
+    /// Stops the pending ticket. Fails with
+    /// [BackfillNotActive](crate::error::GameLiftErrorType::BackfillNotActive)
+    /// if there is none.
+    pub async fn stop(&self) -> Result<(), crate::error::GameLiftErrorType> {
+        self.state.stop_backfill(&self.game_session_id).await
+    }
+
+    /// Returns the pending ticket, if any.
+    pub fn active_ticket(&self) -> Option<BackfillTicket> {
+        self.state.active_backfill_ticket(&self.game_session_id)
+    }
+
+    pub fn ticket(&self, ticket_id: &str) -> Option<BackfillTicket> {
+        self.state.backfill_ticket(&self.game_session_id, ticket_id)
+    }
+
+    /// Returns all tickets of the game session, oldest first.
+    pub fn tickets(&self) -> Vec<BackfillTicket> {
+        self.state.backfill_tickets(&self.game_session_id)
+    }
+
+    /// Starts and stops backfill automatically according to the policy until
+    /// the game session ends or the returned handle is dropped. Run at most
+    /// one policy per game session.
+    pub fn run_policy(
+        &self,
+        policy: crate::backfill_policy::BackfillPolicy,
+    ) -> crate::backfill_policy::BackfillPolicyHandle {
+        crate::backfill_policy::BackfillPolicyHandle::spawn(self.clone(), policy)
+    }
+
+    pub(crate) fn subscribe_notifications(
+        &self,
+    ) -> tokio::sync::broadcast::Receiver<crate::notification::Notification> {
+        self.state.subscribe_notifications()
+    }
+
+    pub(crate) fn player_count(&self) -> usize {
+        self.state.player_count(Some(&self.game_session_id))
+    }
+
+    /// The maximum player count of the game session, if it is limited.
+    pub(crate) fn max_players(&self) -> usize {
+        match self.state.game_session(&self.game_session_id) {
+            Some(game_session) if game_session.max_players > 0 => game_session.max_players as usize,
+            _ => usize::MAX,
+        }
+    }
+
+    /// Whether the game session has ended or is about to.
+    pub(crate) fn is_ending(&self) -> bool {
+        self.state.is_game_session_ending(&self.game_session_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backfill::{backfill_players, BackfillTicketState, BackfillTickets};
+
+    #[test]
+    fn backfill_tickets_test() {
+        let mut tickets = BackfillTickets::default();
+        tickets.start("ticket-1".to_string()).unwrap();
+        assert!(matches!(
+            tickets.start("ticket-2".to_string()),
+            Err(crate::error::GameLiftErrorType::BackfillAlreadyActive(id)) if id == "ticket-1"
+        ));
+
+        assert!(tickets.finish("ticket-1", BackfillTicketState::TimedOut));
+        assert!(!tickets.finish("ticket-1", BackfillTicketState::Cancelled));
+        assert_eq!(tickets.get("ticket-1").unwrap().state, BackfillTicketState::TimedOut);
+        assert!(tickets.active().is_none());
+
+        tickets.start("ticket-2".to_string()).unwrap();
+        tickets.discard("ticket-2");
+        assert_eq!(tickets.tickets().len(), 1);
+    }
+
+    #[test]
+    fn backfill_players_test() {
+        let player = |player_id: &str| crate::entity::MatchmakerPlayer {
+            player_id: player_id.to_string(),
+            attributes: Default::default(),
+        };
+        let matchmaker_data = crate::entity::MatchmakerData {
+            match_id: "match-1".to_string(),
+            matchmaking_configuration_arn: "arn".to_string(),
+            teams: vec![crate::entity::MatchmakerTeam {
+                name: "red".to_string(),
+                players: vec![player("player-1"), player("player-2")],
+            }],
+            auto_backfill_mode: None,
+        };
+        let mut player_sessions = crate::player_session::PlayerSessionRegistry::default();
+        player_sessions.record_description(&crate::entity::PlayerSession {
+            player_id: Some("player-2".to_string()),
+            player_session_id: Some("psess-2".to_string()),
+            game_session_id: Some("gsess-1".to_string()),
+            fleet_id: None,
+            ip_address: None,
+            player_data: None,
+            port: 14000,
+            creation_time: 0,
+            termination_time: 0,
+            status: crate::entity::PlayerSessionStatus::Reserved,
+            dns_name: None,
+        });
+        player_sessions.accept("psess-2").unwrap();
+        player_sessions.remove("psess-2");
+
+        let players = backfill_players(&matchmaker_data, &player_sessions);
+        assert_eq!(players.len(), 1);
+        assert_eq!(players[0].player_id.as_deref(), Some("player-1"));
+        assert_eq!(players[0].team.as_deref(), Some("red"));
+    }
+}
@@ -0,0 +1,231 @@
+use crate::backfill::{BackfillManager, BackfillTicket, BackfillTicketState};
+
+pub type OnBackfillEventOutputType =
+    std::pin::Pin<Box<dyn std::future::Future<Output = ()> + std::marker::Send>>;
+pub type OnBackfillEventType =
+    dyn Fn(BackfillEvent) -> OnBackfillEventOutputType + std::marker::Send + std::marker::Sync;
+
+/// What a [BackfillPolicy] has done.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BackfillEvent {
+    /// Backfill has started because the player count dropped below
+    /// [min_players](BackfillPolicy::min_players).
+    Started(BackfillTicket),
+
+    /// A ticket has left the pending state, e.g. it has been fulfilled or
+    /// stopped because the game session is full.
+    Finished(BackfillTicket),
+
+    /// Starting backfill failed. It is retried like a failed ticket, unless
+    /// retrying cannot help, e.g. because the game session has no valid
+    /// matchmaker data. The policy stops then.
+    StartFailed(crate::error::GameLiftErrorType),
+
+    /// Backfill will be retried after `delay` because the previous attempt
+    /// failed or timed out. `attempt` counts the failures in a row.
+    RetryScheduled { attempt: u32, delay: std::time::Duration },
+}
+
+/// Starts and stops match backfill depending on the number of connected
+/// players, so that the game server only decides the thresholds. Run it with
+/// [BackfillManager::run_policy].
+///
+/// Backfill starts when the player count drops below `min_players` and is
+/// stopped when the game session is full or the process is terminating.
+/// Tickets that fail or time out are retried with exponential backoff, as are
+/// failures to send the request. After a
+/// ticket has been fulfilled, backfill waits for the matched players to
+/// connect, see [settle_delay](Self::settle_delay).
+pub struct BackfillPolicy {
+    /// Backfill starts when fewer players are connected.
+    pub min_players: usize,
+
+    /// Backfill stops when this many players are connected. `None` means the
+    /// maximum player count of the game session.
+    pub max_players: Option<usize>,
+
+    /// Delay before the first retry. It doubles with every failure in a row.
+    pub retry_delay: std::time::Duration,
+
+    /// Upper bound of the retry delay.
+    pub max_retry_delay: std::time::Duration,
+
+    /// How long backfill does not start again after a ticket has been
+    /// fulfilled. The matched players only hold reservations until they
+    /// connect, so the player count stays low meanwhile. GameLift drops
+    /// reservations of players that have not connected within a minute.
+    pub settle_delay: std::time::Duration,
+
+    /// Invoked for every [BackfillEvent]. Events are delivered in order.
+    pub on_event: Option<Box<OnBackfillEventType>>,
+}
+
+impl BackfillPolicy {
+    pub fn new(min_players: usize) -> Self {
+        Self {
+            min_players,
+            max_players: None,
+            retry_delay: std::time::Duration::from_secs(5),
+            max_retry_delay: std::time::Duration::from_secs(120),
+            settle_delay: std::time::Duration::from_secs(60),
+            on_event: None,
+        }
+    }
+
+    /// Returns the delay before retry number `attempt`, starting at 1.
+    fn retry_delay(&self, attempt: u32) -> std::time::Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.retry_delay.saturating_mul(factor).min(self.max_retry_delay)
+    }
+
+    async fn emit(&self, event: BackfillEvent) {
+        if let Some(on_event) = &self.on_event {
+            on_event(event).await;
+        }
+    }
+}
+
+/// A running [BackfillPolicy]. The policy stops when the game session ends,
+/// when the process is terminating, when backfill cannot be started at all, or
+/// when the handle is dropped.
+#[derive(Debug)]
+pub struct BackfillPolicyHandle {
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl BackfillPolicyHandle {
+    pub(crate) fn spawn(manager: BackfillManager, policy: BackfillPolicy) -> Self {
+        Self { task: tokio::spawn(run(manager, policy)) }
+    }
+
+    /// Whether the policy has stopped by itself.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+impl Drop for BackfillPolicyHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn run(manager: BackfillManager, policy: BackfillPolicy) {
+    let mut notifications = manager.subscribe_notifications();
+    let mut failures = 0;
+    let mut retry_at = None;
+
+    loop {
+        if manager.is_ending() {
+            if manager.active_ticket().is_some() {
+                if let Err(error) = manager.stop().await {
+                    log::warn!(
+                        "Could not stop backfill of a terminating game session: {:?}",
+                        error
+                    );
+                }
+            }
+            return;
+        }
+
+        let player_count = manager.player_count();
+        let max_players = policy.max_players.unwrap_or_else(|| manager.max_players());
+        let may_start = retry_at.is_none_or(|retry_at| tokio::time::Instant::now() >= retry_at);
+        match manager.active_ticket() {
+            Some(_) if player_count >= max_players => {
+                if let Err(error) = manager.stop().await {
+                    log::warn!("Could not stop backfill of a full game session: {:?}", error);
+                }
+            }
+            None if player_count < policy.min_players && may_start => {
+                retry_at = None;
+                match manager.start().await {
+                    Ok(ticket) => policy.emit(BackfillEvent::Started(ticket)).await,
+                    Err(error) => {
+                        let retryable = is_retryable(&error);
+                        policy.emit(BackfillEvent::StartFailed(error)).await;
+                        if !retryable {
+                            log::warn!("Backfill cannot be started, stopping the policy.");
+                            return;
+                        }
+                        failures += 1;
+                        retry_at = Some(schedule_retry(&policy, failures).await);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        let retry = async {
+            match retry_at {
+                Some(retry_at) => tokio::time::sleep_until(retry_at).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            notification = notifications.recv() => match notification {
+                Ok(crate::notification::Notification::BackfillTicketUpdated {
+                    game_session_id,
+                    ticket_id,
+                    state,
+                }) if &game_session_id == manager.game_session_id() => {
+                    match state {
+                        BackfillTicketState::Failed | BackfillTicketState::TimedOut => {
+                            failures += 1;
+                            retry_at = Some(schedule_retry(&policy, failures).await);
+                        }
+                        BackfillTicketState::Fulfilled => {
+                            failures = 0;
+                            retry_at = Some(tokio::time::Instant::now() + policy.settle_delay);
+                        }
+                        _ => {}
+                    }
+                    if let Some(ticket) = manager.ticket(&ticket_id) {
+                        policy.emit(BackfillEvent::Finished(ticket)).await;
+                    }
+                }
+                Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {}
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => return,
+            },
+            _ = retry => {}
+        }
+    }
+}
+
+/// Whether starting backfill may succeed later. Errors about the game session
+/// itself stay the same until GameLift sends a new one.
+fn is_retryable(error: &crate::error::GameLiftErrorType) -> bool {
+    !matches!(
+        error,
+        crate::error::GameLiftErrorType::MatchmakerDataNotSet
+            | crate::error::GameLiftErrorType::MalformedMatchmakerData { .. }
+            | crate::error::GameLiftErrorType::GameSessionNotFound(_)
+            | crate::error::GameLiftErrorType::GameSessionIdNotSet
+            | crate::error::GameLiftErrorType::IllegalState { .. }
+    )
+}
+
+async fn schedule_retry(policy: &BackfillPolicy, attempt: u32) -> tokio::time::Instant {
+    let delay = policy.retry_delay(attempt);
+    policy.emit(BackfillEvent::RetryScheduled { attempt, delay }).await;
+    tokio::time::Instant::now() + delay
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backfill_policy::BackfillPolicy;
+
+    #[test]
+    fn retry_delay_test() {
+        let policy = BackfillPolicy {
+            retry_delay: std::time::Duration::from_secs(5),
+            max_retry_delay: std::time::Duration::from_secs(30),
+            ..BackfillPolicy::new(2)
+        };
+        assert_eq!(policy.retry_delay(1), std::time::Duration::from_secs(5));
+        assert_eq!(policy.retry_delay(3), std::time::Duration::from_secs(20));
+        assert_eq!(policy.retry_delay(4), std::time::Duration::from_secs(30));
+        assert_eq!(policy.retry_delay(100), std::time::Duration::from_secs(30));
+    }
+}
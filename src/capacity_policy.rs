@@ -0,0 +1,219 @@
+use crate::entity::PlayerSessionCreationPolicy;
+
+/// Switches the [PlayerSessionCreationPolicy] of a game session between
+/// `AcceptAll` and `DenyAll` depending on the number of connected players.
+/// Run it with
+/// [GameSessionHandle::run_capacity_policy](crate::game_session::GameSessionHandle::run_capacity_policy)
+/// or [Api::run_capacity_policy](crate::api::Api::run_capacity_policy).
+///
+/// New players are denied once the player count reaches the cap, and accepted
+/// again only after it has dropped below `cap - reopen_margin`, so that a
+/// single player joining and leaving does not flip the policy every time. The
+/// player count only includes accepted player sessions, not the ones GameLift
+/// has reserved for players still connecting. New players are always denied
+/// once the process is [draining](crate::api::Api::start_draining).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapacityPolicy {
+    /// Caps the player count below the maximum player count of the game
+    /// session, e.g. to keep slots for reconnecting players.
+    pub soft_cap: Option<usize>,
+
+    /// How many players below the cap the player count stays denied. With the
+    /// default of 1 and a cap of 10, new players are denied at 10 and 9
+    /// players and accepted again at 8. 0 disables the hysteresis.
+    pub reopen_margin: usize,
+
+    /// Delay before a failed policy update is retried.
+    pub retry_delay: std::time::Duration,
+}
+
+impl Default for CapacityPolicy {
+    fn default() -> Self {
+        Self { soft_cap: None, reopen_margin: 1, retry_delay: std::time::Duration::from_secs(5) }
+    }
+}
+
+impl CapacityPolicy {
+    /// Returns the creation policy for `player_count` players, given the one
+    /// in effect.
+    fn decide(
+        &self,
+        player_count: usize,
+        max_players: usize,
+        draining: bool,
+        current: Option<PlayerSessionCreationPolicy>,
+    ) -> PlayerSessionCreationPolicy {
+        let cap = self.soft_cap.map_or(max_players, |soft_cap| soft_cap.min(max_players));
+        if draining || player_count >= cap {
+            PlayerSessionCreationPolicy::DenyAll
+        } else if player_count.saturating_add(self.reopen_margin) < cap {
+            PlayerSessionCreationPolicy::AcceptAll
+        } else {
+            match current {
+                Some(PlayerSessionCreationPolicy::DenyAll) => PlayerSessionCreationPolicy::DenyAll,
+                _ => PlayerSessionCreationPolicy::AcceptAll,
+            }
+        }
+    }
+}
+
+/// A running [CapacityPolicy]. The policy stops when the game session ends,
+/// when the process is terminating, or when the handle is dropped.
+#[derive(Debug)]
+pub struct CapacityPolicyHandle {
+    task: tokio::task::JoinHandle<()>,
+    draining: tokio::sync::watch::Sender<bool>,
+    applied: std::sync::Arc<parking_lot::Mutex<Option<PlayerSessionCreationPolicy>>>,
+}
+
+impl CapacityPolicyHandle {
+    pub(crate) fn spawn(
+        state: std::sync::Arc<crate::server_state::ServerState>,
+        game_session_id: crate::entity::GameSessionId,
+        policy: CapacityPolicy,
+    ) -> Self {
+        let (draining, draining_receiver) = tokio::sync::watch::channel(false);
+        let applied = std::sync::Arc::new(parking_lot::Mutex::new(None));
+        let task =
+            tokio::spawn(run(state, game_session_id, policy, draining_receiver, applied.clone()));
+        Self { task, draining, applied }
+    }
+
+    /// Denies new players regardless of the player count while `draining` is
+    /// set, e.g. before a planned restart. Once it is cleared the policy
+    /// follows the player count again, unless the whole process is
+    /// [draining](crate::api::Api::start_draining).
+    pub fn set_draining(&self, draining: bool) {
+        self.draining.send_replace(draining);
+    }
+
+    pub fn is_draining(&self) -> bool {
+        *self.draining.borrow()
+    }
+
+    /// The creation policy last sent to GameLift, if any.
+    pub fn policy(&self) -> Option<PlayerSessionCreationPolicy> {
+        *self.applied.lock()
+    }
+
+    /// Whether the policy has stopped by itself.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+impl Drop for CapacityPolicyHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn run(
+    state: std::sync::Arc<crate::server_state::ServerState>,
+    game_session_id: crate::entity::GameSessionId,
+    policy: CapacityPolicy,
+    mut draining: tokio::sync::watch::Receiver<bool>,
+    applied: std::sync::Arc<parking_lot::Mutex<Option<PlayerSessionCreationPolicy>>>,
+) {
+    let mut notifications = state.subscribe_notifications();
+    let mut retry_at = None;
+
+    loop {
+        if state.is_game_session_ending(&game_session_id) {
+            return;
+        }
+
+        let max_players = match state.game_session(&game_session_id) {
+            Some(game_session) if game_session.max_players > 0 => game_session.max_players as usize,
+            _ => usize::MAX,
+        };
+        let current = *applied.lock();
+        let desired = policy.decide(
+            state.player_count(Some(&game_session_id)),
+            max_players,
+            *draining.borrow() || state.is_draining(),
+            current,
+        );
+        let may_update = retry_at.is_none_or(|retry_at| tokio::time::Instant::now() >= retry_at);
+        if current != Some(desired) && may_update {
+            match state
+                .update_player_session_creation_policy(Some(game_session_id.clone()), desired)
+                .await
+            {
+                Ok(()) => {
+                    log::info!(
+                        "Set the player session creation policy of the game session {} to {}.",
+                        game_session_id,
+                        desired
+                    );
+                    *applied.lock() = Some(desired);
+                    retry_at = None;
+                }
+                Err(error) => {
+                    log::warn!(
+                        "Could not set the player session creation policy of the game session {} \
+                         to {}: {:?}",
+                        game_session_id,
+                        desired,
+                        error
+                    );
+                    retry_at = Some(tokio::time::Instant::now() + policy.retry_delay);
+                }
+            }
+            continue;
+        }
+
+        let retry = async {
+            match retry_at {
+                Some(retry_at) => tokio::time::sleep_until(retry_at).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            notification = notifications.recv() => {
+                if let Err(tokio::sync::broadcast::error::RecvError::Closed) = notification {
+                    return;
+                }
+            }
+            changed = draining.changed() => {
+                // The handle has been dropped, so the task is about to be
+                // aborted.
+                if changed.is_err() {
+                    return;
+                }
+            }
+            _ = retry => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{capacity_policy::CapacityPolicy, entity::PlayerSessionCreationPolicy};
+
+    #[test]
+    fn decide_test() {
+        let policy = CapacityPolicy { soft_cap: Some(8), reopen_margin: 2, ..Default::default() };
+        let decide = |player_count, draining, current| {
+            policy.decide(player_count, 10, draining, Some(current))
+        };
+        use PlayerSessionCreationPolicy::{AcceptAll, DenyAll};
+
+        assert_eq!(decide(7, false, AcceptAll), AcceptAll);
+        assert_eq!(decide(8, false, AcceptAll), DenyAll);
+        assert_eq!(decide(7, false, DenyAll), DenyAll);
+        assert_eq!(decide(6, false, DenyAll), DenyAll);
+        assert_eq!(decide(5, false, DenyAll), AcceptAll);
+        assert_eq!(decide(0, true, AcceptAll), DenyAll);
+        assert_eq!(policy.decide(7, 10, false, None), AcceptAll);
+        assert_eq!(policy.decide(4, 5, false, None), AcceptAll);
+        assert_eq!(policy.decide(5, 5, false, None), DenyAll);
+
+        let policy = CapacityPolicy::default();
+        assert_eq!(policy.decide(4, 5, false, Some(DenyAll)), DenyAll);
+        assert_eq!(policy.decide(3, 5, false, Some(DenyAll)), AcceptAll);
+
+        let policy = CapacityPolicy { reopen_margin: 0, ..Default::default() };
+        assert_eq!(policy.decide(4, 5, false, Some(DenyAll)), AcceptAll);
+    }
+}
@@ -0,0 +1,161 @@
+use crate::sdk_event::SdkEvent;
+
+/// Feeds the events to the
+/// [ProcessParameters](crate::process_parameters::ProcessParameters)
+/// callbacks. Every callback runs on its own task, so the events are always
+/// consumed promptly; `ordering` decides which callbacks may overlap.
+pub(crate) async fn run_callbacks(
+    process_parameters: crate::process_parameters::ProcessParameters,
+    mut events: crate::sdk_event::SdkEvents,
+    ordering: crate::sdk_config::CallbackOrdering,
+    timeout: Option<std::time::Duration>,
+) {
+    let callbacks = std::sync::Arc::new(process_parameters);
+    let mut queues = std::collections::HashMap::new();
+
+    while let Some(event) = events.recv().await {
+        let queue_key = match ordering {
+            crate::sdk_config::CallbackOrdering::Sequential => None,
+            crate::sdk_config::CallbackOrdering::PerKind => Some(std::mem::discriminant(&event)),
+            crate::sdk_config::CallbackOrdering::Unordered => {
+                tokio::spawn(invoke(callbacks.clone(), event, timeout));
+                continue;
+            }
+        };
+
+        let queue =
+            queues.entry(queue_key).or_insert_with(|| spawn_queue(callbacks.clone(), timeout));
+        if queue.send(event).is_err() {
+            log::error!("A callback queue has stopped unexpectedly.");
+        }
+    }
+}
+
+/// Spawns a task invoking the callbacks for the events sent to the returned
+/// queue one at a time.
+fn spawn_queue(
+    callbacks: std::sync::Arc<crate::process_parameters::ProcessParameters>,
+    timeout: Option<std::time::Duration>,
+) -> tokio::sync::mpsc::UnboundedSender<SdkEvent> {
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(event) = receiver.recv().await {
+            invoke(callbacks.clone(), event, timeout).await;
+        }
+    });
+    sender
+}
+
+async fn invoke(
+    callbacks: std::sync::Arc<crate::process_parameters::ProcessParameters>,
+    event: SdkEvent,
+    timeout: Option<std::time::Duration>,
+) {
+    let callback_name = callback_name(&event);
+    let callback = async {
+        match event {
+            SdkEvent::StartGameSession(game_session) => {
+                (callbacks.on_start_game_session)(game_session).await;
+            }
+            SdkEvent::UpdateGameSession(update_game_session) => {
+                (callbacks.on_update_game_session)(update_game_session).await;
+            }
+            SdkEvent::ProcessTerminate { .. } => {
+                (callbacks.on_process_terminate)().await;
+            }
+            SdkEvent::HealthCheck(responder) => {
+                responder.respond((callbacks.on_health_check)().await);
+            }
+        }
+    };
+
+    match timeout {
+        Some(timeout) => {
+            if tokio::time::timeout(timeout, callback).await.is_err() {
+                log::warn!("The {} callback was cancelled after {:?}.", callback_name, timeout);
+            }
+        }
+        None => callback.await,
+    }
+}
+
+fn callback_name(event: &SdkEvent) -> &'static str {
+    match event {
+        SdkEvent::StartGameSession(_) => "on_start_game_session",
+        SdkEvent::UpdateGameSession(_) => "on_update_game_session",
+        SdkEvent::ProcessTerminate { .. } => "on_process_terminate",
+        SdkEvent::HealthCheck(_) => "on_health_check",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        dispatcher::run_callbacks,
+        sdk_config::CallbackOrdering,
+        sdk_event::{HealthCheckResponder, SdkEvent, SdkEvents},
+    };
+
+    const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+    /// Callbacks where starting a game session never finishes and
+    /// termination is reported to the returned receiver.
+    fn process_parameters(
+    ) -> (crate::process_parameters::ProcessParameters, tokio::sync::mpsc::UnboundedReceiver<()>)
+    {
+        let (terminate_sender, terminate_receiver) = tokio::sync::mpsc::unbounded_channel();
+        let process_parameters = crate::process_parameters::ProcessParameters {
+            on_start_game_session: Box::new(|_| Box::pin(std::future::pending())),
+            on_update_game_session: Box::new(|_| Box::pin(async {})),
+            on_process_terminate: Box::new(move || {
+                let _ = terminate_sender.send(());
+                Box::pin(async {})
+            }),
+            on_health_check: Box::new(|| Box::pin(std::future::pending())),
+            port: 14000,
+            log_parameters: crate::log_parameters::LogParameters { log_paths: vec![] },
+        };
+        (process_parameters, terminate_receiver)
+    }
+
+    fn start(
+        ordering: CallbackOrdering,
+        timeout: Option<std::time::Duration>,
+    ) -> (tokio::sync::mpsc::Sender<SdkEvent>, tokio::sync::mpsc::UnboundedReceiver<()>) {
+        let (process_parameters, terminate_receiver) = process_parameters();
+        let (event_sender, event_receiver) = tokio::sync::mpsc::channel(4);
+        tokio::spawn(run_callbacks(
+            process_parameters,
+            SdkEvents::new(event_receiver),
+            ordering,
+            timeout,
+        ));
+        (event_sender, terminate_receiver)
+    }
+
+    #[tokio::test]
+    async fn per_kind_ordering_test() {
+        let (event_sender, mut terminate_receiver) = start(CallbackOrdering::PerKind, None);
+
+        event_sender
+            .send(SdkEvent::StartGameSession(crate::entity::GameSession::default()))
+            .await
+            .unwrap();
+        event_sender.send(SdkEvent::ProcessTerminate { termination_time: None }).await.unwrap();
+
+        assert!(tokio::time::timeout(TIMEOUT, terminate_receiver.recv()).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn callback_timeout_test() {
+        let (event_sender, mut terminate_receiver) =
+            start(CallbackOrdering::Sequential, Some(std::time::Duration::from_millis(50)));
+
+        let (responder, health) = HealthCheckResponder::new();
+        event_sender.send(SdkEvent::HealthCheck(responder)).await.unwrap();
+        event_sender.send(SdkEvent::ProcessTerminate { termination_time: None }).await.unwrap();
+
+        assert!(tokio::time::timeout(TIMEOUT, health).await.unwrap().is_err());
+        assert!(tokio::time::timeout(TIMEOUT, terminate_receiver.recv()).await.unwrap().is_some());
+    }
+}
@@ -30,6 +30,66 @@ pub struct GameSession {
     pub dns_name: Option<String>,
 }
 
+impl GameSession {
+    /// Parses the FlexMatch matchmaker data of the game session. Returns
+    /// `Ok(None)` if the game session was not created by FlexMatch, and
+    /// [MalformedMatchmakerData](crate::error::GameLiftErrorType::MalformedMatchmakerData)
+    /// naming the offending field if the data cannot be parsed.
+    pub fn matchmaker_data(
+        &self,
+    ) -> Result<Option<MatchmakerData>, crate::error::GameLiftErrorType> {
+        match self.matchmaker_data.as_deref() {
+            Some(json) if !json.is_empty() => crate::matchmaker_data::parse(json).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The result of a FlexMatch matchmaking, see
+/// [GameSession::matchmaker_data](GameSession::matchmaker_data).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchmakerData {
+    pub match_id: String,
+
+    /// The matchmaker that made the match, which is also the one to send
+    /// match backfill requests to.
+    pub matchmaking_configuration_arn: MatchmakingConfigurationArn,
+    pub teams: Vec<MatchmakerTeam>,
+
+    /// `AUTOMATIC` or `MANUAL`, if the matchmaker supports backfill.
+    pub auto_backfill_mode: Option<String>,
+}
+
+impl MatchmakerData {
+    /// Returns the matched players in the form expected by
+    /// [start_match_backfill](crate::api::Api::start_match_backfill).
+    pub fn players(&self) -> Vec<Player> {
+        self.teams
+            .iter()
+            .flat_map(|team| {
+                team.players.iter().map(move |player| Player {
+                    player_id: Some(player.player_id.clone()),
+                    player_attributes: Some(player.attributes.clone()),
+                    team: Some(team.name.clone()),
+                    latency_in_ms: None,
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchmakerTeam {
+    pub name: String,
+    pub players: Vec<MatchmakerPlayer>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchmakerPlayer {
+    pub player_id: PlayerId,
+    pub attributes: std::collections::HashMap<String, AttributeValue>,
+}
+
 #[derive(Debug, Default, Clone, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ActivateGameSession {
@@ -56,11 +116,17 @@ impl Default for UpdateGameSession {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, strum_macros::EnumString)]
 #[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
 pub enum UpdateReason {
+    #[serde(alias = "MATCHMAKING_DATA_UPDATED")]
     MatchmakingDataUpdated,
+    #[serde(alias = "BACKFILL_FAILED")]
     BackfillFailed,
+    #[serde(alias = "BACKFILL_TIMED_OUT")]
     BackfillTimedOut,
+    #[serde(alias = "BACKFILL_CANCELLED")]
     BackfillCancelled,
+    #[serde(other)]
     Unknown,
 }
 
@@ -163,7 +229,8 @@ pub struct PlayerSession {
     pub dns_name: Option<String>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::EnumString)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::EnumString, strum_macros::Display)]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE", ascii_case_insensitive)]
 pub enum PlayerSessionStatus {
     NotSet,
     Reserved,
@@ -227,7 +294,7 @@ pub struct StopMatchBackfillRequest {
     pub matchmaking_configuration_arn: Option<MatchmakingConfigurationArn>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct AttributeValue {
     pub attr_type: AttrType,
     pub s: Option<String>,
@@ -244,7 +311,7 @@ pub enum AttrType {
     StringDoubleMap,
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct GetInstanceCertificateResult {
     pub certificate_path: String,
     pub private_key_path: String,
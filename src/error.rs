@@ -1,11 +1,105 @@
-#[derive(thiserror::Error, Debug, strum_macros::Display)]
+#[derive(thiserror::Error, Debug)]
 pub enum GameLiftErrorType {
+    #[error("the service call failed")]
     ServiceCallFailed,
+    #[error("the local connection failed")]
     LocalConnectionFailed,
+    #[error("the network is not initialized")]
     NetworkNotInitialized,
+    #[error("no game session ID is set")]
     GameSessionIdNotSet,
+    #[error("no termination time is set")]
     TerminationTimeNotSet,
-    BadRequest,
-    InternalServiceError,
-    UnexpectedWebSocketMessage,
+    /// The request was rejected by AuxProxy. Contains the error message sent
+    /// by the server.
+    #[error("bad request: {0}")]
+    BadRequest(String),
+    /// AuxProxy failed to process the request. Contains the error message
+    /// sent by the server.
+    #[error("internal service error: {0}")]
+    InternalServiceError(String),
+    /// A message received from AuxProxy over the WebSocket connection could
+    /// not be decoded. Contains the reason.
+    #[error("unexpected WebSocket message: {0}")]
+    UnexpectedWebSocketMessage(String),
+    /// A response received from AuxProxy could not be decoded. Contains the
+    /// reason.
+    #[error("malformed response: {0}")]
+    MalformedResponse(String),
+    /// The call is not supported by the protocol selected in
+    /// [SdkConfig](crate::sdk_config::SdkConfig).
+    #[error("the operation is not supported by the protocol")]
+    UnsupportedOperation,
+    /// The operation is not allowed in the current
+    /// [ProcessState](crate::server_state::ProcessState), e.g. activating a
+    /// game session twice. Nothing was sent to GameLift.
+    #[error("{operation} is not allowed in the {state} state")]
+    IllegalState { operation: &'static str, state: crate::server_state::ProcessState },
+    /// The process hosts several game sessions, so the call needs a
+    /// [GameSessionHandle](crate::game_session::GameSessionHandle) to tell
+    /// which one it targets.
+    #[error("the process hosts several game sessions, pick one")]
+    AmbiguousGameSession,
+    /// The process does not host a game session with this ID.
+    #[error("game session {0} not found")]
+    GameSessionNotFound(crate::entity::GameSessionId),
+    /// The process already hosts
+    /// [max_concurrent_game_sessions](crate::sdk_config::SdkConfig::max_concurrent_game_sessions)
+    /// game sessions. Contains the maximum.
+    #[error("the process already hosts the maximum of {0} game sessions")]
+    GameSessionCapacityReached(usize),
+    /// The process already hosts a game session with this ID.
+    #[error("game session {0} is already hosted")]
+    DuplicateGameSession(crate::entity::GameSessionId),
+    /// The player session has already been accepted, and maybe removed since.
+    /// Nothing was sent to GameLift.
+    #[error("player session {0} has already been accepted")]
+    PlayerSessionAlreadyAccepted(crate::entity::PlayerSessionId),
+    /// The matchmaker data of a game session could not be parsed. `path`
+    /// points at the offending field, e.g. `teams[0].players[1].playerId`.
+    #[error("malformed matchmaker data at {path}: {reason}")]
+    MalformedMatchmakerData { path: String, reason: String },
+    /// The game session was not created by FlexMatch, so it cannot be
+    /// backfilled.
+    #[error("the game session has no matchmaker data")]
+    MatchmakerDataNotSet,
+    /// A match backfill ticket of the game session is still pending. Contains
+    /// its ID.
+    #[error("backfill ticket {0} is still pending")]
+    BackfillAlreadyActive(crate::entity::TicketId),
+    /// No match backfill ticket of the game session is pending.
+    #[error("no backfill ticket is pending")]
+    BackfillNotActive,
+    /// The client has not sent its player session ID within
+    /// [handshake_timeout](crate::gatekeeper::GatekeeperConfig::handshake_timeout).
+    #[error("the client handshake timed out")]
+    HandshakeTimedOut,
+    /// The player session ID could not be read from the client. Contains the
+    /// reason.
+    #[error("the client handshake failed: {0}")]
+    HandshakeFailed(String),
+    /// The instance certificate could not be loaded, or the private key or
+    /// hostname do not match it. Contains the reason.
+    #[error("invalid instance certificate: {0}")]
+    InvalidInstanceCertificate(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::GameLiftErrorType;
+
+    #[test]
+    fn display_test() {
+        let error = GameLiftErrorType::BadRequest("Invalid player session".to_string());
+        assert!(error.to_string().contains("Invalid player session"));
+
+        let error = GameLiftErrorType::MalformedMatchmakerData {
+            path: "teams[0].players[1].playerId".to_string(),
+            reason: "expected a string".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "malformed matchmaker data at teams[0].players[1].playerId: expected a string"
+        );
+    }
 }
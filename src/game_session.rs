@@ -0,0 +1,177 @@
+/// A handle to a game session hosted by the server process. Get it with
+/// [Api::game_session](crate::api::Api::game_session) or
+/// [Api::game_sessions](crate::api::Api::game_sessions). Unlike the
+/// [Api](crate::api::Api) methods it always targets its own game session, so
+/// it is the way to go when
+/// [max_concurrent_game_sessions](crate::sdk_config::SdkConfig::max_concurrent_game_sessions)
+/// is greater than one.
+#[derive(Clone)]
+pub struct GameSessionHandle {
+    state: std::sync::Arc<crate::server_state::ServerState>,
+    game_session_id: crate::entity::GameSessionId,
+}
+
+impl std::fmt::Debug for GameSessionHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GameSessionHandle").field("game_session_id", &self.game_session_id).finish()
+    }
+}
+
+impl GameSessionHandle {
+    pub(crate) fn new(
+        state: std::sync::Arc<crate::server_state::ServerState>,
+        game_session_id: crate::entity::GameSessionId,
+    ) -> Self {
+        Self { state, game_session_id }
+    }
+
+    pub fn game_session_id(&self) -> &crate::entity::GameSessionId {
+        &self.game_session_id
+    }
+
+    /// Returns the state of the game session, or `None` if the process no
+    /// longer hosts it.
+    pub fn state(&self) -> Option<crate::server_state::GameSessionState> {
+        self.state.game_session_state(&self.game_session_id)
+    }
+
+    /// Returns the game session GameLift has started, or `None` if the
+    /// process no longer hosts it.
+    pub fn game_session(&self) -> Option<crate::entity::GameSession> {
+        self.state.game_session(&self.game_session_id)
+    }
+
+    /// Returns the number of connected players, i.e. of player sessions that
+    /// have been accepted but not removed yet.
+    pub fn player_count(&self) -> usize {
+        self.state.player_count(Some(&self.game_session_id))
+    }
+
+    /// Returns the player sessions of the game session known to the process.
+    pub fn player_sessions(&self) -> Vec<crate::player_session::PlayerSessionRecord> {
+        self.state.player_sessions(&self.game_session_id)
+    }
+
+    pub fn player_session(
+        &self,
+        player_session_id: &str,
+    ) -> Option<crate::player_session::PlayerSessionRecord> {
+        self.state.player_session(&self.game_session_id, player_session_id)
+    }
+
+    /// Streams the player sessions of the game session from GameLift,
+    /// optionally only those with the given status. See
+    /// [describe_player_sessions_stream](crate::api::Api::describe_player_sessions_stream).
+    pub fn describe_player_sessions(
+        &self,
+        status_filter: Option<crate::entity::PlayerSessionStatus>,
+    ) -> impl futures_util::Stream<
+        Item = Result<crate::entity::PlayerSession, crate::error::GameLiftErrorType>,
+    > + Send
+           + 'static {
+        crate::player_session::describe_stream(
+            self.state.clone(),
+            crate::entity::DescribePlayerSessionsRequest {
+                game_session_id: Some(self.game_session_id.clone()),
+                player_session_status_filter: status_filter.map(|status| status.to_string()),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Manages the player session creation policy of the game session
+    /// according to the player count, until the returned handle is dropped.
+    pub fn run_capacity_policy(
+        &self,
+        policy: crate::capacity_policy::CapacityPolicy,
+    ) -> crate::capacity_policy::CapacityPolicyHandle {
+        crate::capacity_policy::CapacityPolicyHandle::spawn(
+            self.state.clone(),
+            self.game_session_id.clone(),
+            policy,
+        )
+    }
+
+    /// Ends the game session once it is idle according to the policy, until
+    /// the returned handle is dropped.
+    pub fn run_idle_policy(
+        &self,
+        policy: crate::idle_policy::IdlePolicy,
+    ) -> crate::idle_policy::IdlePolicyHandle {
+        crate::idle_policy::IdlePolicyHandle::spawn(
+            self.state.clone(),
+            self.game_session_id.clone(),
+            policy,
+        )
+    }
+
+    /// Returns the match backfill manager of the game session.
+    pub fn backfill(&self) -> crate::backfill::BackfillManager {
+        crate::backfill::BackfillManager::new(self.state.clone(), self.game_session_id.clone())
+    }
+
+    /// Notifies the GameLift service that the game session is activated and
+    /// ready to receive player connections.
+    pub async fn activate(&self) -> Result<(), crate::error::GameLiftErrorType> {
+        self.state.activate_game_session(Some(self.game_session_id.clone())).await
+    }
+
+    /// Notifies the GameLift service that the game session has ended, which
+    /// frees its slot for a new game session. Not supported by the
+    /// [WebSocket](crate::sdk_config::Protocol::WebSocket) protocol.
+    pub async fn terminate(&self) -> Result<(), crate::error::GameLiftErrorType> {
+        self.state.terminate_game_session(Some(self.game_session_id.clone())).await
+    }
+
+    /// Updates the ability of the game session to accept new player sessions.
+    pub async fn update_player_session_creation_policy(
+        &self,
+        player_session_policy: crate::entity::PlayerSessionCreationPolicy,
+    ) -> Result<(), crate::error::GameLiftErrorType> {
+        self.state
+            .update_player_session_creation_policy(
+                Some(self.game_session_id.clone()),
+                player_session_policy,
+            )
+            .await
+    }
+
+    /// Notifies the GameLift service that a player with the specified player
+    /// session ID has connected to the game session and needs validation.
+    /// Fails without calling GameLift if the player session has been accepted
+    /// before.
+    pub async fn accept_player_session(
+        &self,
+        player_session_id: crate::entity::PlayerSessionId,
+    ) -> Result<(), crate::error::GameLiftErrorType> {
+        self.state
+            .accept_player_session(Some(self.game_session_id.clone()), player_session_id)
+            .await
+    }
+
+    /// Notifies the GameLift service that a player with the specified player
+    /// session ID has disconnected from the game session.
+    pub async fn remove_player_session(
+        &self,
+        player_session_id: crate::entity::PlayerSessionId,
+    ) -> Result<(), crate::error::GameLiftErrorType> {
+        self.state
+            .remove_player_session(Some(self.game_session_id.clone()), player_session_id)
+            .await
+    }
+
+    /// Returns a [Gatekeeper](crate::gatekeeper::Gatekeeper) that admits
+    /// players into the game session.
+    pub fn gatekeeper<S>(
+        &self,
+        config: crate::gatekeeper::GatekeeperConfig,
+        handshake: Box<crate::gatekeeper::HandshakeType<S>>,
+    ) -> crate::gatekeeper::Gatekeeper<S> {
+        crate::gatekeeper::Gatekeeper::new(
+            self.state.clone(),
+            Some(self.game_session_id.clone()),
+            config,
+            handshake,
+        )
+    }
+}
@@ -0,0 +1,266 @@
+pub type HandshakeOutputType<S> = std::pin::Pin<
+    Box<
+        dyn std::future::Future<Output = std::io::Result<(S, crate::entity::PlayerSessionId)>>
+            + std::marker::Send,
+    >,
+>;
+pub type HandshakeType<S> =
+    dyn Fn(S) -> HandshakeOutputType<S> + std::marker::Send + std::marker::Sync;
+
+/// The longest line accepted by [line_handshake]. Player session IDs are much
+/// shorter.
+pub const MAX_HANDSHAKE_LINE_LENGTH: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GatekeeperConfig {
+    /// How long a client has to send its player session ID.
+    pub handshake_timeout: std::time::Duration,
+}
+
+impl Default for GatekeeperConfig {
+    fn default() -> Self {
+        Self { handshake_timeout: std::time::Duration::from_secs(10) }
+    }
+}
+
+/// A handshake that reads the player session ID as the first line sent by the
+/// client, terminated by `\n` or `\r\n`. It reads byte by byte, so nothing
+/// sent after the line is consumed.
+pub fn line_handshake<S>() -> Box<HandshakeType<S>>
+where
+    S: tokio::io::AsyncRead + std::marker::Unpin + std::marker::Send + 'static,
+{
+    Box::new(|mut stream: S| {
+        Box::pin(async move {
+            use tokio::io::AsyncReadExt;
+
+            let mut line = Vec::new();
+            loop {
+                match stream.read_u8().await? {
+                    b'\n' => break,
+                    _ if line.len() == MAX_HANDSHAKE_LINE_LENGTH => {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            "the handshake line is too long",
+                        ));
+                    }
+                    byte => line.push(byte),
+                }
+            }
+            let line = String::from_utf8(line)
+                .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+            Ok((stream, line.trim_end_matches('\r').to_string()))
+        })
+    })
+}
+
+/// Admits players into a game session: it reads the player session ID from a
+/// new connection with the handshake, validates it with
+/// [accept_player_session](crate::api::Api::accept_player_session) and wraps
+/// the connection into a [PlayerConnection], which removes the player session
+/// when dropped. Get it with [Api::gatekeeper](crate::api::Api::gatekeeper)
+/// or [GameSessionHandle::gatekeeper](crate::game_session::GameSessionHandle::gatekeeper).
+pub struct Gatekeeper<S> {
+    state: std::sync::Arc<crate::server_state::ServerState>,
+    game_session_id: Option<crate::entity::GameSessionId>,
+    config: GatekeeperConfig,
+    handshake: Box<HandshakeType<S>>,
+}
+
+impl<S> std::fmt::Debug for Gatekeeper<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Gatekeeper")
+            .field("game_session_id", &self.game_session_id)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl<S> Gatekeeper<S> {
+    pub(crate) fn new(
+        state: std::sync::Arc<crate::server_state::ServerState>,
+        game_session_id: Option<crate::entity::GameSessionId>,
+        config: GatekeeperConfig,
+        handshake: Box<HandshakeType<S>>,
+    ) -> Self {
+        Self { state, game_session_id, config, handshake }
+    }
+
+    /// Performs the handshake on a new connection and accepts the player
+    /// session. The connection is closed if this fails: with
+    /// [HandshakeTimedOut](crate::error::GameLiftErrorType::HandshakeTimedOut)
+    /// or [HandshakeFailed](crate::error::GameLiftErrorType::HandshakeFailed)
+    /// if no player session ID could be read, otherwise with the error of
+    /// GameLift, e.g. for an unknown or expired player session.
+    pub async fn admit(
+        &self,
+        stream: S,
+    ) -> Result<PlayerConnection<S>, crate::error::GameLiftErrorType> {
+        let (stream, player_session_id) =
+            tokio::time::timeout(self.config.handshake_timeout, (self.handshake)(stream))
+                .await
+                .map_err(|_| crate::error::GameLiftErrorType::HandshakeTimedOut)?
+                .map_err(|error| {
+                    crate::error::GameLiftErrorType::HandshakeFailed(error.to_string())
+                })?;
+        if player_session_id.is_empty() {
+            return Err(crate::error::GameLiftErrorType::HandshakeFailed(
+                "the player session ID is empty".to_string(),
+            ));
+        }
+
+        self.state
+            .accept_player_session(self.game_session_id.clone(), player_session_id.clone())
+            .await?;
+        Ok(PlayerConnection {
+            stream,
+            player_session_id,
+            state: self.state.clone(),
+            game_session_id: self.game_session_id.clone(),
+            removed: false,
+        })
+    }
+}
+
+/// A connection of a player whose player session has been accepted. It can be
+/// used as the underlying stream. The player session is removed by
+/// [close](Self::close) or, in the background, when the connection is dropped.
+pub struct PlayerConnection<S> {
+    stream: S,
+    player_session_id: crate::entity::PlayerSessionId,
+    state: std::sync::Arc<crate::server_state::ServerState>,
+    game_session_id: Option<crate::entity::GameSessionId>,
+    removed: bool,
+}
+
+impl<S> std::fmt::Debug for PlayerConnection<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PlayerConnection")
+            .field("player_session_id", &self.player_session_id)
+            .field("game_session_id", &self.game_session_id)
+            .finish()
+    }
+}
+
+impl<S> PlayerConnection<S> {
+    pub fn player_session_id(&self) -> &crate::entity::PlayerSessionId {
+        &self.player_session_id
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Removes the player session and closes the connection. Unlike dropping
+    /// the connection it reports whether GameLift has been notified.
+    pub async fn close(mut self) -> Result<(), crate::error::GameLiftErrorType> {
+        let result = self
+            .state
+            .remove_player_session(self.game_session_id.clone(), self.player_session_id.clone())
+            .await;
+        // Dropping the connection while this is pending removes it in the
+        // background instead.
+        self.removed = true;
+        result
+    }
+}
+
+impl<S> Drop for PlayerConnection<S> {
+    fn drop(&mut self) {
+        if self.removed {
+            return;
+        }
+        let state = self.state.clone();
+        let game_session_id = self.game_session_id.take();
+        let player_session_id = std::mem::take(&mut self.player_session_id);
+        let runtime = match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => runtime,
+            Err(_) => {
+                log::warn!(
+                    "Cannot remove the player session {} outside of a tokio runtime.",
+                    player_session_id
+                );
+                return;
+            }
+        };
+        runtime.spawn(async move {
+            if let Err(error) =
+                state.remove_player_session(game_session_id, player_session_id.clone()).await
+            {
+                log::warn!(
+                    "Could not remove the player session {}: {:?}",
+                    player_session_id,
+                    error
+                );
+            }
+        });
+    }
+}
+
+impl<S> tokio::io::AsyncRead for PlayerConnection<S>
+where
+    S: tokio::io::AsyncRead + std::marker::Unpin,
+{
+    fn poll_read(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut self.get_mut().stream).poll_read(cx, buf)
+    }
+}
+
+impl<S> tokio::io::AsyncWrite for PlayerConnection<S>
+where
+    S: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    fn poll_write(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        std::pin::Pin::new(&mut self.get_mut().stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut self.get_mut().stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut self.get_mut().stream).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gatekeeper::line_handshake;
+
+    #[tokio::test]
+    async fn line_handshake_test() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let handshake = line_handshake();
+        let (mut client, server) = tokio::io::duplex(64);
+        client.write_all(b"psess-1\r\nhello").await.unwrap();
+        let (mut server, player_session_id) = handshake(server).await.unwrap();
+        assert_eq!(player_session_id, "psess-1");
+        let mut rest = [0; 5];
+        server.read_exact(&mut rest).await.unwrap();
+        assert_eq!(&rest, b"hello");
+
+        let (mut client, server) = tokio::io::duplex(64);
+        client.write_all(b"psess-1").await.unwrap();
+        drop(client);
+        assert_eq!(handshake(server).await.unwrap_err().kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+}
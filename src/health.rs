@@ -0,0 +1,332 @@
+pub type HealthCheckOutputType =
+    std::pin::Pin<Box<dyn std::future::Future<Output = HealthCheckResult> + std::marker::Send>>;
+pub type HealthCheckType =
+    dyn Fn() -> HealthCheckOutputType + std::marker::Send + std::marker::Sync;
+
+/// Name of the entry of the `on_health_check` callback in a [HealthSnapshot].
+pub const ON_HEALTH_CHECK: &str = "on_health_check";
+
+/// The outcome of a single run of a health check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthCheckResult {
+    pub healthy: bool,
+
+    /// Why the check has failed, or any other detail worth reporting.
+    pub reason: Option<String>,
+}
+
+impl HealthCheckResult {
+    pub fn healthy() -> Self {
+        Self { healthy: true, reason: None }
+    }
+
+    pub fn unhealthy(reason: impl Into<String>) -> Self {
+        Self { healthy: false, reason: Some(reason.into()) }
+    }
+}
+
+/// How many results in a row it takes to change the health of a check, so
+/// that a single hiccup does not mark the process unhealthy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hysteresis {
+    /// Failures in a row after which a healthy check becomes unhealthy.
+    pub failure_threshold: u32,
+
+    /// Successes in a row after which an unhealthy check becomes healthy.
+    pub success_threshold: u32,
+}
+
+impl Default for Hysteresis {
+    fn default() -> Self {
+        Self { failure_threshold: 1, success_threshold: 1 }
+    }
+}
+
+/// The health of a single check after the last run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthCheckStatus {
+    pub name: String,
+
+    /// The health after applying the [Hysteresis].
+    pub healthy: bool,
+    pub last_result: HealthCheckResult,
+    pub consecutive_failures: u32,
+    pub consecutive_successes: u32,
+}
+
+impl HealthCheckStatus {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            healthy: true,
+            last_result: HealthCheckResult::healthy(),
+            consecutive_failures: 0,
+            consecutive_successes: 0,
+        }
+    }
+
+    fn record(&mut self, result: HealthCheckResult, hysteresis: Hysteresis) {
+        if result.healthy {
+            self.consecutive_successes += 1;
+            self.consecutive_failures = 0;
+            if self.consecutive_successes >= hysteresis.success_threshold {
+                self.healthy = true;
+            }
+        } else {
+            self.consecutive_failures += 1;
+            self.consecutive_successes = 0;
+            if self.consecutive_failures >= hysteresis.failure_threshold {
+                self.healthy = false;
+            }
+        }
+        self.last_result = result;
+    }
+}
+
+/// The result of a health report, see
+/// [Api::health_snapshot](crate::api::Api::health_snapshot).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthSnapshot {
+    /// What has been reported to GameLift: whether all checks are healthy.
+    pub healthy: bool,
+    pub checked_at: std::time::SystemTime,
+
+    /// The registered checks in the order of registration, followed by
+    /// [ON_HEALTH_CHECK].
+    pub checks: Vec<HealthCheckStatus>,
+}
+
+impl HealthSnapshot {
+    /// Returns the checks that are unhealthy.
+    pub fn unhealthy_checks(&self) -> impl Iterator<Item = &HealthCheckStatus> {
+        self.checks.iter().filter(|status| !status.healthy)
+    }
+}
+
+struct RegisteredCheck {
+    check: std::sync::Arc<HealthCheckType>,
+    hysteresis: Hysteresis,
+    status: HealthCheckStatus,
+}
+
+#[derive(Default)]
+struct RegistryState {
+    checks: Vec<RegisteredCheck>,
+    callback_status: Option<HealthCheckStatus>,
+    snapshot: Option<HealthSnapshot>,
+}
+
+/// Health checks of the subsystems of the game server, e.g. the game loop or a
+/// database pool. Every health report runs all of them together with
+/// `on_health_check`, and the process is reported healthy only if all of them
+/// are. Get it with [Api::health_registry](crate::api::Api::health_registry);
+/// all clones share the same checks.
+#[derive(Clone, Default)]
+pub struct HealthRegistry {
+    state: std::sync::Arc<parking_lot::Mutex<RegistryState>>,
+}
+
+impl std::fmt::Debug for HealthRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<_> =
+            self.state.lock().checks.iter().map(|check| check.status.name.clone()).collect();
+        f.debug_struct("HealthRegistry").field("checks", &names).finish()
+    }
+}
+
+impl HealthRegistry {
+    /// Registers a check that is unhealthy as soon as it fails once. A check
+    /// with the same name is replaced.
+    pub fn register(&self, name: impl Into<String>, check: Box<HealthCheckType>) {
+        self.register_with_hysteresis(name, Hysteresis::default(), check);
+    }
+
+    pub fn register_with_hysteresis(
+        &self,
+        name: impl Into<String>,
+        hysteresis: Hysteresis,
+        check: Box<HealthCheckType>,
+    ) {
+        self.register_shared(name.into(), hysteresis, check.into());
+    }
+
+    /// Registers a check the caller keeps a reference to, so that it can
+    /// [unregister_check](Self::unregister_check) exactly this check later.
+    pub(crate) fn register_shared(
+        &self,
+        name: String,
+        hysteresis: Hysteresis,
+        check: std::sync::Arc<HealthCheckType>,
+    ) {
+        let registered =
+            RegisteredCheck { check, hysteresis, status: HealthCheckStatus::new(name.clone()) };
+        let mut state = self.state.lock();
+        match state.checks.iter_mut().find(|check| check.status.name == name) {
+            Some(check) => *check = registered,
+            None => state.checks.push(registered),
+        }
+    }
+
+    /// Removes a check. Returns `false` if there is no check with this name.
+    pub fn unregister(&self, name: &str) -> bool {
+        let mut state = self.state.lock();
+        let count = state.checks.len();
+        state.checks.retain(|check| check.status.name != name);
+        state.checks.len() != count
+    }
+
+    /// Removes the check `name` only if it is still `check`, not one that has
+    /// replaced it since.
+    pub(crate) fn unregister_check(
+        &self,
+        name: &str,
+        check: &std::sync::Arc<HealthCheckType>,
+    ) -> bool {
+        let mut state = self.state.lock();
+        let count = state.checks.len();
+        state.checks.retain(|registered| {
+            registered.status.name != name || !std::sync::Arc::ptr_eq(&registered.check, check)
+        });
+        state.checks.len() != count
+    }
+
+    /// Returns the result of the last health report, if any.
+    pub fn snapshot(&self) -> Option<HealthSnapshot> {
+        self.state.lock().snapshot.clone()
+    }
+
+    /// Runs all checks and the `on_health_check` callback concurrently, each
+    /// limited to `timeout`, and stores the result.
+    pub(crate) async fn run_checks(
+        &self,
+        timeout: std::time::Duration,
+        callback: impl std::future::Future<Output = bool>,
+    ) -> HealthSnapshot {
+        let checks: Vec<_> = self
+            .state
+            .lock()
+            .checks
+            .iter()
+            .map(|check| (check.status.name.clone(), check.check.clone()))
+            .collect();
+        let results =
+            futures_util::future::join_all(checks.into_iter().map(|(name, check)| async move {
+                let result = tokio::time::timeout(timeout, check())
+                    .await
+                    .unwrap_or_else(|_| HealthCheckResult::unhealthy("timed out"));
+                (name, result)
+            }));
+        let callback = async {
+            match tokio::time::timeout(timeout, callback).await {
+                Ok(true) => HealthCheckResult::healthy(),
+                Ok(false) => HealthCheckResult::unhealthy("reported unhealthy"),
+                Err(_) => HealthCheckResult::unhealthy("timed out"),
+            }
+        };
+        let (results, callback_result) = tokio::join!(results, callback);
+
+        let mut state = self.state.lock();
+        for (name, result) in results {
+            // The check may have been unregistered in the meantime.
+            if let Some(check) = state.checks.iter_mut().find(|check| check.status.name == name) {
+                check.status.record(result, check.hysteresis);
+            }
+        }
+        let callback_status = state
+            .callback_status
+            .get_or_insert_with(|| HealthCheckStatus::new(ON_HEALTH_CHECK.to_string()));
+        callback_status.record(callback_result, Hysteresis::default());
+
+        let checks: Vec<_> = state
+            .checks
+            .iter()
+            .map(|check| check.status.clone())
+            .chain(state.callback_status.clone())
+            .collect();
+        let snapshot = HealthSnapshot {
+            healthy: checks.iter().all(|status| status.healthy),
+            checked_at: std::time::SystemTime::now(),
+            checks,
+        };
+        state.snapshot = Some(snapshot.clone());
+        snapshot
+    }
+}
+
+/// Returns a random duration up to `jitter`.
+pub(crate) fn random_jitter(jitter: std::time::Duration) -> std::time::Duration {
+    use rand::Rng;
+
+    if jitter.is_zero() {
+        return jitter;
+    }
+    rand::thread_rng().gen_range(std::time::Duration::ZERO..=jitter)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::health::{HealthCheckResult, HealthRegistry, Hysteresis, ON_HEALTH_CHECK};
+
+    const TIMEOUT: std::time::Duration = std::time::Duration::from_millis(50);
+
+    #[test]
+    fn random_jitter_test() {
+        let jitter = std::time::Duration::from_millis(5);
+        let samples: Vec<_> = (0..1000).map(|_| crate::health::random_jitter(jitter)).collect();
+        assert!(samples.iter().all(|sample| *sample <= jitter));
+        assert!(samples.iter().any(|sample| *sample != samples[0]));
+        assert_eq!(
+            crate::health::random_jitter(std::time::Duration::ZERO),
+            std::time::Duration::ZERO
+        );
+    }
+
+    #[tokio::test]
+    async fn hysteresis_test() {
+        let registry = HealthRegistry::default();
+        let healthy = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let check_healthy = healthy.clone();
+        registry.register_with_hysteresis(
+            "database",
+            Hysteresis { failure_threshold: 2, success_threshold: 1 },
+            Box::new(move || {
+                let healthy = check_healthy.load(std::sync::atomic::Ordering::SeqCst);
+                Box::pin(async move {
+                    if healthy {
+                        HealthCheckResult::healthy()
+                    } else {
+                        HealthCheckResult::unhealthy("no connection")
+                    }
+                })
+            }),
+        );
+
+        let snapshot = registry.run_checks(TIMEOUT, async { true }).await;
+        assert!(snapshot.healthy);
+        assert_eq!(snapshot.checks[0].consecutive_failures, 1);
+
+        let snapshot = registry.run_checks(TIMEOUT, async { true }).await;
+        assert!(!snapshot.healthy);
+        let unhealthy: Vec<_> = snapshot.unhealthy_checks().collect();
+        assert_eq!(unhealthy.len(), 1);
+        assert_eq!(unhealthy[0].last_result.reason.as_deref(), Some("no connection"));
+
+        healthy.store(true, std::sync::atomic::Ordering::SeqCst);
+        assert!(registry.run_checks(TIMEOUT, async { true }).await.healthy);
+        assert_eq!(registry.snapshot().unwrap().checks[1].name, ON_HEALTH_CHECK);
+    }
+
+    #[tokio::test]
+    async fn timeout_test() {
+        let registry = HealthRegistry::default();
+        registry.register("game_loop", Box::new(|| Box::pin(std::future::pending())));
+
+        let snapshot = registry.run_checks(TIMEOUT, std::future::pending()).await;
+        assert!(!snapshot.healthy);
+        assert!(snapshot.checks.iter().all(|status| !status.healthy));
+
+        assert!(registry.unregister("game_loop"));
+        assert!(!registry.unregister("game_loop"));
+        assert!(registry.run_checks(TIMEOUT, async { true }).await.healthy);
+    }
+}
@@ -1,55 +1,73 @@
+use crate::protos::generated_with_pure::sdk::GameLiftResponse_Status;
 use protobuf::RepeatedField;
-use reqwest::Response;
+
+/// The envelope AuxProxy may wrap a response into. Mirrors `GameLiftResponse`
+/// from `sdk.proto`, but tolerates omitted default fields.
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GameLiftResponseEnvelope {
+    status: GameLiftResponse_Status,
+    #[serde(default)]
+    response_data: String,
+    #[serde(default)]
+    error_message: String,
+}
 
 pub struct HttpClient {
-    uri: reqwest::Url,
+    uri: String,
     http_client: reqwest::Client,
+    max_concurrent_game_sessions: u32,
 }
 
-impl Default for HttpClient {
-    fn default() -> Self {
+impl HttpClient {
+    pub fn new(config: &crate::sdk_config::SdkConfig) -> Self {
         use reqwest::header;
         let mut headers = header::HeaderMap::new();
         headers.insert(header::ACCEPT, header::HeaderValue::from_static("application/json"));
         headers.insert(
             header::HeaderName::from_static("gamelift-server-pid"),
-            header::HeaderValue::from_str(std::process::id().to_string().as_str())
-                .expect("Cannot parse a gamelift-server-pid header value"),
+            header::HeaderValue::from(config.pid),
         );
 
+        let mut builder = reqwest::ClientBuilder::new().default_headers(headers);
+        if let Some(request_timeout) = config.request_timeout {
+            builder = builder.timeout(request_timeout);
+        }
+        if let Some(connect_timeout) = config.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+
         Self {
-            uri: reqwest::Url::parse("http://localhost:5758/")
-                .expect("Cannot parse GameLift Server URI"),
-            http_client: reqwest::ClientBuilder::new()
-                .default_headers(headers)
-                .build()
-                .expect("Cannot build HTTP client"),
+            uri: config.http_uri(),
+            http_client: builder.build().expect("Cannot build HTTP client"),
+            max_concurrent_game_sessions: config.max_concurrent_game_sessions,
         }
     }
-}
 
-impl HttpClient {
-    async fn send<T>(&self, message: T) -> Result<Response, crate::error::GameLiftErrorType>
+    /// Sends the message to AuxProxy and returns the response payload.
+    async fn send<T>(&self, message: T) -> Result<String, crate::error::GameLiftErrorType>
     where
         T: protobuf::Message,
     {
-        let message_as_bytes = message.write_to_bytes().unwrap();
-        let message_header =
-            get_message_type(&message).expect("Cannot extract the message header").to_string();
+        let message_as_bytes = message
+            .write_to_bytes()
+            .map_err(|error| crate::error::GameLiftErrorType::BadRequest(error.to_string()))?;
+        let message_header = get_message_type(&message)
+            .ok_or(crate::error::GameLiftErrorType::ServiceCallFailed)?
+            .to_string();
         log::debug!("Message name: {}", message_header);
-        self.http_client
-            .post(self.uri.clone())
+        let response = self
+            .http_client
+            .post(self.uri.as_str())
             .header("gamelift-target", message_header)
             .body(message_as_bytes)
             .send()
             .await
-            .map_err(|error| {
-                if error.status().is_some() && error.status().unwrap().is_server_error() {
-                    crate::error::GameLiftErrorType::InternalServiceError
-                } else {
-                    crate::error::GameLiftErrorType::BadRequest
-                }
-            })
+            .map_err(map_transport_error)?;
+
+        let status = response.status();
+        let body = response.text().await.map_err(map_transport_error)?;
+        parse_response(status, body)
     }
 
     pub async fn process_ready(
@@ -60,6 +78,12 @@ impl HttpClient {
         let message = crate::protos::generated_with_pure::sdk::ProcessReady {
             port,
             logPathsToUpload: RepeatedField::from_vec(log_paths_to_upload),
+            // Left unset for a single game session, like other SDKs do.
+            maxConcurrentGameSessions: if self.max_concurrent_game_sessions > 1 {
+                self.max_concurrent_game_sessions as i32
+            } else {
+                0
+            },
             ..Default::default()
         };
 
@@ -153,39 +177,28 @@ impl HttpClient {
         &self,
         request: crate::entity::DescribePlayerSessionsRequest,
     ) -> Result<crate::entity::DescribePlayerSessionsResult, crate::error::GameLiftErrorType> {
-        let response = self.send(crate::mapper::describe_player_sessions_mapper(request)).await;
-
-        match response {
-            Ok(response) => {
-                let proto_response: crate::protos::generated_with_pure::sdk::DescribePlayerSessionsResponse =
-                    serde_json::from_str(response.text().await.unwrap().as_str()).unwrap();
-                Ok(crate::mapper::describe_player_session_request_mapper(proto_response))
-            }
-            Err(error) => Err(error),
-        }
+        let response = self.send(crate::mapper::describe_player_sessions_mapper(request)).await?;
+        let proto_response: crate::protos::generated_with_pure::sdk::DescribePlayerSessionsResponse =
+            parse_payload(&response)?;
+        crate::mapper::describe_player_session_request_mapper(proto_response)
     }
 
     pub async fn backfill_matchmaking(
         &self,
         request: crate::entity::StartMatchBackfillRequest,
     ) -> Result<crate::entity::StartMatchBackfillResult, crate::error::GameLiftErrorType> {
-        let response = self.send(crate::mapper::start_match_backfill_request_mapper(request)).await;
-
-        match response {
-            Ok(response) => {
-                let p: crate::protos::generated_with_pure::sdk::BackfillMatchmakingResponse =
-                    serde_json::from_str(response.text().await.unwrap().as_str()).unwrap();
-                Ok(crate::mapper::start_matchmaking_result_mapper(p))
-            }
-            Err(error) => Err(error),
-        }
+        let response =
+            self.send(crate::mapper::start_match_backfill_request_mapper(request)?).await?;
+        let p: crate::protos::generated_with_pure::sdk::BackfillMatchmakingResponse =
+            parse_payload(&response)?;
+        Ok(crate::mapper::start_matchmaking_result_mapper(p))
     }
 
     pub async fn stop_matchmaking(
         &self,
         request: crate::entity::StopMatchBackfillRequest,
     ) -> Result<(), crate::error::GameLiftErrorType> {
-        self.send(crate::mapper::stop_matchmaking_request_mapper(request)).await.map(|_| ())
+        self.send(crate::mapper::stop_matchmaking_request_mapper(request)?).await.map(|_| ())
     }
 
     pub async fn get_instance_certificate(
@@ -193,16 +206,69 @@ impl HttpClient {
     ) -> Result<crate::entity::GetInstanceCertificateResult, crate::error::GameLiftErrorType> {
         let response = self
             .send(crate::protos::generated_with_pure::sdk::GetInstanceCertificate::default())
-            .await;
+            .await?;
+        let p: crate::protos::generated_with_pure::sdk::GetInstanceCertificateResponse =
+            parse_payload(&response)?;
+        Ok(crate::mapper::get_instance_certificate_result_mapper(p))
+    }
+}
 
-        match response {
-            Ok(response) => {
-                let p: crate::protos::generated_with_pure::sdk::GetInstanceCertificateResponse =
-                    serde_json::from_str(response.text().await.unwrap().as_str()).unwrap();
-                Ok(crate::mapper::get_instance_certificate_result_mapper(p))
-            }
-            Err(error) => Err(error),
+fn parse_payload<T>(payload: &str) -> Result<T, crate::error::GameLiftErrorType>
+where
+    T: serde::de::DeserializeOwned,
+{
+    serde_json::from_str(payload).map_err(|error| {
+        crate::error::GameLiftErrorType::MalformedResponse(format!(
+            "Cannot decode the response ({}): {}",
+            error, payload
+        ))
+    })
+}
+
+fn map_transport_error(error: reqwest::Error) -> crate::error::GameLiftErrorType {
+    match error.status() {
+        Some(status) if status.is_server_error() => {
+            crate::error::GameLiftErrorType::InternalServiceError(error.to_string())
         }
+        _ => crate::error::GameLiftErrorType::BadRequest(error.to_string()),
+    }
+}
+
+/// Turns the HTTP status and the body of an AuxProxy response into the response
+/// payload or an error. The body is either a [GameLiftResponseEnvelope] or the
+/// payload itself.
+fn parse_response(
+    status: reqwest::StatusCode,
+    body: String,
+) -> Result<String, crate::error::GameLiftErrorType> {
+    let envelope = serde_json::from_str::<GameLiftResponseEnvelope>(&body).ok();
+
+    if status.is_client_error() || status.is_server_error() {
+        let error_message = match envelope {
+            Some(envelope) if !envelope.error_message.is_empty() => envelope.error_message,
+            _ if body.is_empty() => status.to_string(),
+            _ => body,
+        };
+        log::debug!("AuxProxy responded with {}: {}", status, error_message);
+
+        return Err(if status.is_server_error() {
+            crate::error::GameLiftErrorType::InternalServiceError(error_message)
+        } else {
+            crate::error::GameLiftErrorType::BadRequest(error_message)
+        });
+    }
+
+    match envelope {
+        Some(envelope) => match envelope.status {
+            GameLiftResponse_Status::OK => Ok(envelope.response_data),
+            GameLiftResponse_Status::ERROR_400 => {
+                Err(crate::error::GameLiftErrorType::BadRequest(envelope.error_message))
+            }
+            GameLiftResponse_Status::ERROR_500 => {
+                Err(crate::error::GameLiftErrorType::InternalServiceError(envelope.error_message))
+            }
+        },
+        None => Ok(body),
     }
 }
 
@@ -213,7 +279,11 @@ fn get_message_type<T>(_: &T) -> Option<&str> {
 
 #[cfg(test)]
 mod tests {
-    use crate::http_client::get_message_type;
+    use crate::{
+        error::GameLiftErrorType,
+        http_client::{get_message_type, parse_payload, parse_response},
+    };
+    use reqwest::StatusCode;
 
     #[test]
     fn get_message_type_test() {
@@ -221,4 +291,60 @@ mod tests {
 
         assert_eq!(get_message_type(&process_ready), Some("ProcessReady"));
     }
+
+    #[test]
+    fn parse_response_plain_payload_test() {
+        let body = r#"{"ticketId":"ticket"}"#.to_string();
+
+        assert_eq!(parse_response(StatusCode::OK, body.clone()).unwrap(), body);
+    }
+
+    #[test]
+    fn parse_response_envelope_ok_test() {
+        let body = r#"{"status":"OK","responseData":"{\"ticketId\":\"ticket\"}"}"#.to_string();
+
+        assert_eq!(parse_response(StatusCode::OK, body).unwrap(), r#"{"ticketId":"ticket"}"#);
+    }
+
+    #[test]
+    fn parse_response_envelope_error_test() {
+        let body = r#"{"status":"ERROR_400","errorMessage":"Invalid player session"}"#.to_string();
+
+        assert!(matches!(
+            parse_response(StatusCode::OK, body),
+            Err(GameLiftErrorType::BadRequest(message)) if message == "Invalid player session"
+        ));
+
+        let body = r#"{"status":"ERROR_500","errorMessage":"Oops"}"#.to_string();
+
+        assert!(matches!(
+            parse_response(StatusCode::OK, body),
+            Err(GameLiftErrorType::InternalServiceError(message)) if message == "Oops"
+        ));
+    }
+
+    #[test]
+    fn parse_response_http_error_test() {
+        let body = r#"{"status":"ERROR_400","errorMessage":"Unknown game session"}"#.to_string();
+
+        assert!(matches!(
+            parse_response(StatusCode::BAD_REQUEST, body),
+            Err(GameLiftErrorType::BadRequest(message)) if message == "Unknown game session"
+        ));
+
+        assert!(matches!(
+            parse_response(StatusCode::INTERNAL_SERVER_ERROR, "Failure".to_string()),
+            Err(GameLiftErrorType::InternalServiceError(message)) if message == "Failure"
+        ));
+    }
+
+    #[test]
+    fn parse_payload_non_json_test() {
+        let result: Result<
+            crate::protos::generated_with_pure::sdk::BackfillMatchmakingResponse,
+            _,
+        > = parse_payload("<html>Bad Gateway</html>");
+
+        assert!(matches!(result, Err(GameLiftErrorType::MalformedResponse(_))));
+    }
 }
@@ -0,0 +1,199 @@
+pub type OnIdleOutputType =
+    std::pin::Pin<Box<dyn std::future::Future<Output = bool> + std::marker::Send>>;
+pub type OnIdleType =
+    dyn Fn(IdleContext) -> OnIdleOutputType + std::marker::Send + std::marker::Sync;
+pub type BeforeEndingOutputType =
+    std::pin::Pin<Box<dyn std::future::Future<Output = ()> + std::marker::Send>>;
+pub type BeforeEndingType =
+    dyn Fn(IdleContext) -> BeforeEndingOutputType + std::marker::Send + std::marker::Sync;
+
+/// Why a game session is considered idle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+pub enum IdleReason {
+    /// No player session has been accepted since the game session was
+    /// activated.
+    NoPlayerJoined,
+
+    /// All players have left.
+    Empty,
+}
+
+/// Passed to the [IdlePolicy] hooks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdleContext {
+    pub game_session_id: crate::entity::GameSessionId,
+    pub reason: IdleReason,
+
+    /// How long the game session has been idle.
+    pub idle_for: std::time::Duration,
+}
+
+/// Ends game sessions nobody plays in. Both timeouts are off by default. Run
+/// it with
+/// [GameSessionHandle::run_idle_policy](crate::game_session::GameSessionHandle::run_idle_policy)
+/// or [Api::run_idle_policy](crate::api::Api::run_idle_policy).
+///
+/// An idle game session is ended with
+/// [process_ending](crate::api::Api::process_ending) if the process hosts a
+/// single game session at a time, otherwise it is terminated so that its slot
+/// is freed.
+#[derive(Default)]
+pub struct IdlePolicy {
+    /// Ends the game session if no player session has been accepted this long
+    /// after the activation.
+    pub no_player_timeout: Option<std::time::Duration>,
+
+    /// Ends the game session if the player count has been zero for this long
+    /// after the last player has left.
+    pub empty_timeout: Option<std::time::Duration>,
+
+    /// Invoked when a timeout expires. Return `true` to keep the game session,
+    /// then the timeout starts over.
+    pub veto: Option<Box<OnIdleType>>,
+
+    /// Invoked right before the game session is ended.
+    pub before_ending: Option<Box<BeforeEndingType>>,
+}
+
+impl IdlePolicy {
+    fn timeout(&self, reason: IdleReason) -> Option<std::time::Duration> {
+        match reason {
+            IdleReason::NoPlayerJoined => self.no_player_timeout,
+            IdleReason::Empty => self.empty_timeout,
+        }
+    }
+}
+
+/// A running [IdlePolicy]. The policy stops when the game session ends, when
+/// the process is terminating, or when the handle is dropped.
+#[derive(Debug)]
+pub struct IdlePolicyHandle {
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl IdlePolicyHandle {
+    pub(crate) fn spawn(
+        state: std::sync::Arc<crate::server_state::ServerState>,
+        game_session_id: crate::entity::GameSessionId,
+        policy: IdlePolicy,
+    ) -> Self {
+        Self { task: tokio::spawn(run(state, game_session_id, policy)) }
+    }
+
+    /// Whether the policy has stopped by itself, e.g. after ending the game
+    /// session.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+impl Drop for IdlePolicyHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn idle_reason(
+    state: &crate::server_state::ServerState,
+    game_session_id: &str,
+) -> Option<IdleReason> {
+    if state.game_session_state(game_session_id)
+        != Some(crate::server_state::GameSessionState::Active)
+    {
+        None
+    } else if state
+        .player_sessions(game_session_id)
+        .iter()
+        .all(|record| record.accepted_at.is_none())
+    {
+        // Described player sessions may be reserved, but nobody has joined.
+        Some(IdleReason::NoPlayerJoined)
+    } else if state.player_count(Some(game_session_id)) == 0 {
+        Some(IdleReason::Empty)
+    } else {
+        None
+    }
+}
+
+async fn run(
+    state: std::sync::Arc<crate::server_state::ServerState>,
+    game_session_id: crate::entity::GameSessionId,
+    policy: IdlePolicy,
+) {
+    let mut notifications = state.subscribe_notifications();
+    let mut idle_since: Option<(IdleReason, tokio::time::Instant)> = None;
+
+    loop {
+        if state.is_game_session_ending(&game_session_id) {
+            return;
+        }
+        let reason = idle_reason(&state, &game_session_id);
+        if idle_since.map(|(since_reason, _)| since_reason) != reason {
+            idle_since = reason.map(|reason| (reason, tokio::time::Instant::now()));
+        }
+
+        let deadline = idle_since
+            .and_then(|(reason, since)| policy.timeout(reason).map(|timeout| since + timeout));
+        let expired = async {
+            match deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            notification = notifications.recv() => {
+                if let Err(tokio::sync::broadcast::error::RecvError::Closed) = notification {
+                    return;
+                }
+            }
+            _ = expired => {
+                let Some((reason, since)) = idle_since else { continue };
+                let context = IdleContext {
+                    game_session_id: game_session_id.clone(),
+                    reason,
+                    idle_for: since.elapsed(),
+                };
+                if let Some(veto) = &policy.veto {
+                    if veto(context.clone()).await {
+                        log::info!(
+                            "Keeping the idle game session {} ({}).",
+                            game_session_id,
+                            reason
+                        );
+                        idle_since = Some((reason, tokio::time::Instant::now()));
+                        continue;
+                    }
+                }
+                // A player may have joined while the veto hook was running.
+                if state.is_game_session_ending(&game_session_id)
+                    || idle_reason(&state, &game_session_id) != Some(reason)
+                {
+                    continue;
+                }
+
+                log::info!(
+                    "Ending the game session {} after {:?} ({}).",
+                    game_session_id,
+                    context.idle_for,
+                    reason
+                );
+                if let Some(before_ending) = &policy.before_ending {
+                    before_ending(context).await;
+                }
+                let result = if state.max_concurrent_game_sessions() == 1 {
+                    state.process_ending().await
+                } else {
+                    state.terminate_game_session(Some(game_session_id.clone())).await
+                };
+                if let Err(error) = result {
+                    log::error!(
+                        "Could not end the idle game session {}: {:?}",
+                        game_session_id,
+                        error
+                    );
+                }
+                return;
+            }
+        }
+    }
+}
@@ -3,12 +3,35 @@
 //! Official documentation for the SDK (C# version), can be found [here](https://docs.aws.amazon.com/gamelift/latest/developerguide/integration-server-sdk-csharp-ref.html).
 
 pub mod api;
+pub mod backfill;
+pub mod backfill_policy;
+pub mod capacity_policy;
+mod dispatcher;
 pub mod entity;
 pub mod error;
+pub mod game_session;
+pub mod gatekeeper;
+pub mod health;
 mod http_client;
+pub mod idle_policy;
 pub mod log_parameters;
 mod mapper;
+mod matchmaker_data;
+pub mod notification;
+pub mod player_session;
 pub mod process_parameters;
 pub mod protos;
+pub mod sdk_config;
+pub mod sdk_event;
+pub mod server_parameters;
 pub mod server_state;
+pub mod shutdown;
+pub mod termination;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "tls")]
+pub mod tls;
+mod transport;
+pub mod watchdog;
+mod web_socket_client;
 mod web_socket_listener;
@@ -1,6 +1,10 @@
-use crate::entity::GameProperty;
+use crate::{entity::GameProperty, error::GameLiftErrorType};
 use protobuf::RepeatedField;
 
+fn required<T>(value: Option<T>, field_name: &str) -> Result<T, GameLiftErrorType> {
+    value.ok_or_else(|| GameLiftErrorType::BadRequest(format!("{} is required", field_name)))
+}
+
 pub fn game_session_mapper(
     source_game_session: crate::protos::generated_with_pure::sdk::GameSession,
 ) -> crate::entity::GameSession {
@@ -33,18 +37,29 @@ pub fn game_session_mapper(
 #[allow(dead_code)]
 pub fn update_game_session_mapper(
     source: crate::protos::generated_with_pure::sdk::UpdateGameSession,
-) -> crate::entity::UpdateGameSession {
+) -> Result<crate::entity::UpdateGameSession, GameLiftErrorType> {
     use std::str::FromStr;
-    crate::entity::UpdateGameSession {
-        game_session: Some(game_session_mapper(source.gameSession.unwrap())),
-        update_reason: crate::entity::UpdateReason::from_str(&source.updateReason).unwrap(),
+    let game_session = source.gameSession.into_option().ok_or_else(|| {
+        GameLiftErrorType::MalformedResponse("UpdateGameSession without gameSession".to_string())
+    })?;
+    let update_reason =
+        crate::entity::UpdateReason::from_str(&source.updateReason).map_err(|_| {
+            GameLiftErrorType::MalformedResponse(format!(
+                "Unknown update reason: {}",
+                source.updateReason
+            ))
+        })?;
+
+    Ok(crate::entity::UpdateGameSession {
+        game_session: Some(game_session_mapper(game_session)),
+        update_reason,
         backfill_ticket_id: source.backfillTicketId,
-    }
+    })
 }
 
 pub fn describe_player_session_request_mapper(
     source: crate::protos::generated_with_pure::sdk::DescribePlayerSessionsResponse,
-) -> crate::entity::DescribePlayerSessionsResult {
+) -> Result<crate::entity::DescribePlayerSessionsResult, GameLiftErrorType> {
     let mut result = crate::entity::DescribePlayerSessionsResult {
         player_sessions: vec![],
         next_token: source.nextToken,
@@ -52,6 +67,13 @@ pub fn describe_player_session_request_mapper(
 
     use std::str::FromStr;
     for player_session in source.playerSessions {
+        let status =
+            crate::entity::PlayerSessionStatus::from_str(&player_session.status).map_err(|_| {
+                GameLiftErrorType::MalformedResponse(format!(
+                    "Unknown player session status: {}",
+                    player_session.status
+                ))
+            })?;
         let converted_player_session = crate::entity::PlayerSession {
             player_id: Some(player_session.playerId),
             player_session_id: Some(player_session.playerSessionId),
@@ -62,14 +84,14 @@ pub fn describe_player_session_request_mapper(
             port: player_session.port,
             creation_time: player_session.creationTime,
             termination_time: player_session.terminationTime,
-            status: crate::entity::PlayerSessionStatus::from_str(&player_session.status).unwrap(),
+            status,
             dns_name: Some(player_session.dnsName),
         };
 
         result.player_sessions.push(converted_player_session);
     }
 
-    result
+    Ok(result)
 }
 
 pub fn start_matchmaking_result_mapper(
@@ -92,46 +114,49 @@ pub fn get_instance_certificate_result_mapper(
 
 pub fn stop_matchmaking_request_mapper(
     source: crate::entity::StopMatchBackfillRequest,
-) -> crate::protos::generated_with_pure::sdk::StopMatchmakingRequest {
-    crate::protos::generated_with_pure::sdk::StopMatchmakingRequest {
-        ticketId: source.ticket_id.unwrap(),
-        gameSessionArn: source.game_session_arn.unwrap(),
-        matchmakingConfigurationArn: source.matchmaking_configuration_arn.unwrap(),
+) -> Result<crate::protos::generated_with_pure::sdk::StopMatchmakingRequest, GameLiftErrorType> {
+    Ok(crate::protos::generated_with_pure::sdk::StopMatchmakingRequest {
+        ticketId: required(source.ticket_id, "ticket_id")?,
+        gameSessionArn: required(source.game_session_arn, "game_session_arn")?,
+        matchmakingConfigurationArn: required(
+            source.matchmaking_configuration_arn,
+            "matchmaking_configuration_arn",
+        )?,
         ..Default::default()
-    }
+    })
 }
 
 pub fn attribute_value_mapper(
     source: crate::entity::AttributeValue,
-) -> crate::protos::generated_with_pure::sdk::AttributeValue {
+) -> Result<crate::protos::generated_with_pure::sdk::AttributeValue, GameLiftErrorType> {
     let mut result = crate::protos::generated_with_pure::sdk::AttributeValue {
         field_type: source.attr_type as i32,
         ..Default::default()
     };
     match source.attr_type {
         crate::entity::AttrType::String => {
-            result.S = source.s.unwrap();
+            result.S = required(source.s, "s")?;
         }
         crate::entity::AttrType::Double => {
-            result.N = source.n.unwrap();
+            result.N = required(source.n, "n")?;
         }
         crate::entity::AttrType::StringList => {
-            result.SL = RepeatedField::from_vec(source.sl.unwrap());
+            result.SL = RepeatedField::from_vec(required(source.sl, "sl")?);
         }
         crate::entity::AttrType::StringDoubleMap => {
-            result.SDM = source.sdm.unwrap();
+            result.SDM = required(source.sdm, "sdm")?;
         }
     }
 
-    result
+    Ok(result)
 }
 
 pub fn player_mapper(
     source: crate::entity::Player,
-) -> crate::protos::generated_with_pure::sdk::Player {
+) -> Result<crate::protos::generated_with_pure::sdk::Player, GameLiftErrorType> {
     let mut result = crate::protos::generated_with_pure::sdk::Player {
-        playerId: source.player_id.unwrap(),
-        team: source.team.unwrap(),
+        playerId: required(source.player_id, "player_id")?,
+        team: source.team.unwrap_or_default(),
         ..Default::default()
     };
 
@@ -141,30 +166,42 @@ pub fn player_mapper(
 
     if let Some(player_attributes) = source.player_attributes {
         for (id, player_attribute) in player_attributes {
-            result.playerAttributes.insert(id, attribute_value_mapper(player_attribute));
+            let player_attribute =
+                attribute_value_mapper(player_attribute).map_err(|error| match error {
+                    GameLiftErrorType::BadRequest(message) => GameLiftErrorType::BadRequest(
+                        format!("player attribute {}: {}", id, message),
+                    ),
+                    error => error,
+                })?;
+            result.playerAttributes.insert(id, player_attribute);
         }
     }
 
-    result
+    Ok(result)
 }
 
 pub fn start_match_backfill_request_mapper(
     source: crate::entity::StartMatchBackfillRequest,
-) -> crate::protos::generated_with_pure::sdk::BackfillMatchmakingRequest {
+) -> Result<crate::protos::generated_with_pure::sdk::BackfillMatchmakingRequest, GameLiftErrorType>
+{
     let mut result = crate::protos::generated_with_pure::sdk::BackfillMatchmakingRequest {
-        ticketId: source.ticket_id.unwrap(),
-        gameSessionArn: source.game_session_arn.unwrap(),
-        matchmakingConfigurationArn: source.matchmaking_configuration_arn.unwrap(),
+        // GameLift generates a ticket ID if none is provided.
+        ticketId: source.ticket_id.unwrap_or_default(),
+        gameSessionArn: required(source.game_session_arn, "game_session_arn")?,
+        matchmakingConfigurationArn: required(
+            source.matchmaking_configuration_arn,
+            "matchmaking_configuration_arn",
+        )?,
         ..Default::default()
     };
 
     if let Some(players) = source.players {
         for player in players {
-            result.players.push(player_mapper(player));
+            result.players.push(player_mapper(player)?);
         }
     }
 
-    result
+    Ok(result)
 }
 
 pub fn describe_player_sessions_mapper(
@@ -197,3 +234,111 @@ pub fn describe_player_sessions_mapper(
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        entity::{AttrType, AttributeValue, Player, StartMatchBackfillRequest},
+        error::GameLiftErrorType,
+        mapper::{
+            describe_player_session_request_mapper, start_match_backfill_request_mapper,
+            update_game_session_mapper,
+        },
+        protos::generated_with_pure::sdk,
+    };
+
+    fn backfill_request() -> StartMatchBackfillRequest {
+        StartMatchBackfillRequest {
+            ticket_id: None,
+            game_session_arn: Some("arn:game-session".to_string()),
+            matchmaking_configuration_arn: Some("arn:matchmaker".to_string()),
+            players: Some(vec![Player {
+                player_id: Some("player".to_string()),
+                ..Default::default()
+            }]),
+        }
+    }
+
+    #[test]
+    fn start_match_backfill_request_mapper_test() {
+        let result = start_match_backfill_request_mapper(backfill_request()).unwrap();
+
+        assert_eq!(result.ticketId, "");
+        assert_eq!(result.players.len(), 1);
+        assert_eq!(result.players[0].team, "");
+    }
+
+    #[test]
+    fn start_match_backfill_request_mapper_missing_fields_test() {
+        let request = StartMatchBackfillRequest { game_session_arn: None, ..backfill_request() };
+        assert!(matches!(
+            start_match_backfill_request_mapper(request),
+            Err(GameLiftErrorType::BadRequest(message)) if message.contains("game_session_arn")
+        ));
+
+        let request = StartMatchBackfillRequest {
+            players: Some(vec![Player::default()]),
+            ..backfill_request()
+        };
+        assert!(matches!(
+            start_match_backfill_request_mapper(request),
+            Err(GameLiftErrorType::BadRequest(message)) if message.contains("player_id")
+        ));
+    }
+
+    #[test]
+    fn start_match_backfill_request_mapper_missing_attribute_value_test() {
+        let attribute =
+            AttributeValue { attr_type: AttrType::Double, s: None, n: None, sl: None, sdm: None };
+        let request = StartMatchBackfillRequest {
+            players: Some(vec![Player {
+                player_id: Some("player".to_string()),
+                player_attributes: Some([("skill".to_string(), attribute)].into_iter().collect()),
+                ..Default::default()
+            }]),
+            ..backfill_request()
+        };
+
+        assert!(matches!(
+            start_match_backfill_request_mapper(request),
+            Err(GameLiftErrorType::BadRequest(message)) if message.contains("skill")
+        ));
+    }
+
+    #[test]
+    fn describe_player_session_request_mapper_status_test() {
+        let mut response = sdk::DescribePlayerSessionsResponse::default();
+        response
+            .playerSessions
+            .push(sdk::PlayerSession { status: "ACTIVE".to_string(), ..Default::default() });
+        let result = describe_player_session_request_mapper(response.clone()).unwrap();
+        assert_eq!(result.player_sessions[0].status, crate::entity::PlayerSessionStatus::Active);
+
+        response.playerSessions[0].status = "GONE".to_string();
+        assert!(matches!(
+            describe_player_session_request_mapper(response),
+            Err(GameLiftErrorType::MalformedResponse(_))
+        ));
+    }
+
+    #[test]
+    fn update_game_session_mapper_test() {
+        let source = sdk::UpdateGameSession {
+            updateReason: "MATCHMAKING_DATA_UPDATED".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            update_game_session_mapper(source.clone()),
+            Err(GameLiftErrorType::MalformedResponse(_))
+        ));
+
+        let source = sdk::UpdateGameSession {
+            gameSession: protobuf::SingularPtrField::some(sdk::GameSession::default()),
+            ..source
+        };
+        assert_eq!(
+            update_game_session_mapper(source).unwrap().update_reason,
+            crate::entity::UpdateReason::MatchmakingDataUpdated
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! Parses the matchmaker data of FlexMatch game sessions. The parsing is done
+//! by hand to be able to report the path of a malformed field.
+
+use crate::{
+    entity::{AttrType, AttributeValue, MatchmakerData, MatchmakerPlayer, MatchmakerTeam},
+    error::GameLiftErrorType,
+};
+use serde_json::Value;
+
+pub fn parse(json: &str) -> Result<MatchmakerData, GameLiftErrorType> {
+    let root: Value =
+        serde_json::from_str(json).map_err(|error| GameLiftErrorType::MalformedMatchmakerData {
+            path: String::new(),
+            reason: error.to_string(),
+        })?;
+    let root = Field::root(&root);
+
+    let teams = root
+        .field("teams")?
+        .array()?
+        .into_iter()
+        .map(|team| team_mapper(&team))
+        .collect::<Result<_, _>>()?;
+
+    Ok(MatchmakerData {
+        match_id: root.field("matchId")?.string()?,
+        matchmaking_configuration_arn: root.field("matchmakingConfigurationArn")?.string()?,
+        teams,
+        auto_backfill_mode: root
+            .optional_field("autoBackfillMode")
+            .map(|mode| mode.string())
+            .transpose()?,
+    })
+}
+
+fn team_mapper(team: &Field) -> Result<MatchmakerTeam, GameLiftErrorType> {
+    let players = team
+        .field("players")?
+        .array()?
+        .into_iter()
+        .map(|player| player_mapper(&player))
+        .collect::<Result<_, _>>()?;
+    Ok(MatchmakerTeam { name: team.field("name")?.string()?, players })
+}
+
+fn player_mapper(player: &Field) -> Result<MatchmakerPlayer, GameLiftErrorType> {
+    let attributes = match player.optional_field("attributes") {
+        Some(attributes) => attributes
+            .object()?
+            .into_iter()
+            .map(|(name, attribute)| Ok((name, attribute_value_mapper(&attribute)?)))
+            .collect::<Result<_, GameLiftErrorType>>()?,
+        None => Default::default(),
+    };
+    Ok(MatchmakerPlayer { player_id: player.field("playerId")?.string()?, attributes })
+}
+
+fn attribute_value_mapper(attribute: &Field) -> Result<AttributeValue, GameLiftErrorType> {
+    let attribute_type = attribute.field("attributeType")?;
+    let value = attribute.field("valueAttribute")?;
+    let mut result =
+        AttributeValue { attr_type: AttrType::String, s: None, n: None, sl: None, sdm: None };
+    match attribute_type.string()?.as_str() {
+        "STRING" => result.s = Some(value.string()?),
+        "DOUBLE" => {
+            result.attr_type = AttrType::Double;
+            result.n = Some(value.number()?);
+        }
+        "STRING_LIST" => {
+            result.attr_type = AttrType::StringList;
+            result.sl = Some(value.array()?.iter().map(Field::string).collect::<Result<_, _>>()?);
+        }
+        "STRING_DOUBLE_MAP" => {
+            result.attr_type = AttrType::StringDoubleMap;
+            result.sdm = Some(
+                value
+                    .object()?
+                    .into_iter()
+                    .map(|(key, value)| Ok((key, value.number()?)))
+                    .collect::<Result<_, GameLiftErrorType>>()?,
+            );
+        }
+        other => return Err(attribute_type.error(format!("unknown attribute type {}", other))),
+    }
+
+    Ok(result)
+}
+
+/// A JSON value together with its path from the root.
+struct Field<'a> {
+    path: String,
+    value: &'a Value,
+}
+
+impl<'a> Field<'a> {
+    fn root(value: &'a Value) -> Self {
+        Self { path: String::new(), value }
+    }
+
+    fn child(&self, name: &str) -> String {
+        if self.path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", self.path, name)
+        }
+    }
+
+    fn error(&self, reason: impl Into<String>) -> GameLiftErrorType {
+        GameLiftErrorType::MalformedMatchmakerData {
+            path: self.path.clone(),
+            reason: reason.into(),
+        }
+    }
+
+    fn optional_field(&self, name: &str) -> Option<Field<'a>> {
+        match self.value.get(name) {
+            None | Some(Value::Null) => None,
+            Some(value) => Some(Field { path: self.child(name), value }),
+        }
+    }
+
+    fn field(&self, name: &str) -> Result<Field<'a>, GameLiftErrorType> {
+        self.optional_field(name).ok_or_else(|| GameLiftErrorType::MalformedMatchmakerData {
+            path: self.child(name),
+            reason: "missing".to_string(),
+        })
+    }
+
+    fn string(&self) -> Result<String, GameLiftErrorType> {
+        self.value.as_str().map(str::to_string).ok_or_else(|| self.error("expected a string"))
+    }
+
+    fn number(&self) -> Result<f64, GameLiftErrorType> {
+        self.value.as_f64().ok_or_else(|| self.error("expected a number"))
+    }
+
+    fn array(&self) -> Result<Vec<Field<'a>>, GameLiftErrorType> {
+        let values = self.value.as_array().ok_or_else(|| self.error("expected an array"))?;
+        Ok(values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| Field { path: format!("{}[{}]", self.path, index), value })
+            .collect())
+    }
+
+    fn object(&self) -> Result<Vec<(String, Field<'a>)>, GameLiftErrorType> {
+        let values = self.value.as_object().ok_or_else(|| self.error("expected an object"))?;
+        Ok(values
+            .iter()
+            .map(|(key, value)| (key.clone(), Field { path: self.child(key), value }))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        entity::{AttrType, GameSession},
+        error::GameLiftErrorType,
+        matchmaker_data::parse,
+    };
+
+    const MATCHMAKER_DATA: &str = r#"{
+        "matchId": "1111aaaa-22bb-33cc-44dd-5555eeee66ff",
+        "matchmakingConfigurationArn": "arn:aws:gamelift:us-west-2:111122223333:matchmakingconfiguration/MyMatchmakerConfig",
+        "teams": [
+            {
+                "name": "red",
+                "players": [
+                    {
+                        "playerId": "player-1",
+                        "attributes": {
+                            "skill": { "attributeType": "DOUBLE", "valueAttribute": 23 },
+                            "modes": { "attributeType": "STRING_LIST", "valueAttribute": ["ctf"] },
+                            "stats": {
+                                "attributeType": "STRING_DOUBLE_MAP",
+                                "valueAttribute": { "Body": 10.0 }
+                            }
+                        }
+                    }
+                ]
+            },
+            { "name": "blue", "players": [{ "playerId": "player-2" }] }
+        ],
+        "autoBackfillMode": "MANUAL"
+    }"#;
+
+    #[test]
+    fn matchmaker_data_test() {
+        let game_session = GameSession {
+            matchmaker_data: Some(MATCHMAKER_DATA.to_string()),
+            ..Default::default()
+        };
+        let matchmaker_data = game_session.matchmaker_data().unwrap().unwrap();
+        assert_eq!(matchmaker_data.match_id, "1111aaaa-22bb-33cc-44dd-5555eeee66ff");
+        assert_eq!(matchmaker_data.auto_backfill_mode.as_deref(), Some("MANUAL"));
+        assert_eq!(matchmaker_data.teams.len(), 2);
+
+        let attributes = &matchmaker_data.teams[0].players[0].attributes;
+        assert_eq!(attributes["skill"].attr_type, AttrType::Double);
+        assert_eq!(attributes["skill"].n, Some(23.0));
+        assert_eq!(attributes["modes"].sl, Some(vec!["ctf".to_string()]));
+        assert_eq!(attributes["stats"].sdm.as_ref().unwrap()["Body"], 10.0);
+
+        let players = matchmaker_data.players();
+        assert_eq!(players[1].player_id.as_deref(), Some("player-2"));
+        assert_eq!(players[1].team.as_deref(), Some("blue"));
+
+        assert!(GameSession::default().matchmaker_data().unwrap().is_none());
+    }
+
+    #[test]
+    fn malformed_matchmaker_data_test() {
+        let malformed =
+            MATCHMAKER_DATA.replace("\"valueAttribute\": 23", "\"valueAttribute\": \"23\"");
+        assert!(matches!(
+            parse(&malformed),
+            Err(GameLiftErrorType::MalformedMatchmakerData { path, .. })
+                if path == "teams[0].players[0].attributes.skill.valueAttribute"
+        ));
+
+        let malformed =
+            MATCHMAKER_DATA.replace("\"playerId\": \"player-2\"", "\"id\": \"player-2\"");
+        assert!(matches!(
+            parse(&malformed),
+            Err(GameLiftErrorType::MalformedMatchmakerData { path, .. })
+                if path == "teams[1].players[0].playerId"
+        ));
+
+        assert!(matches!(
+            parse("{"),
+            Err(GameLiftErrorType::MalformedMatchmakerData { path, .. }) if path.is_empty()
+        ));
+    }
+}
@@ -0,0 +1,56 @@
+/// Notifications about SDK-internal events that do not require an answer from
+/// the game server. Subscribe to them with
+/// [subscribe_notifications](crate::api::Api::subscribe_notifications).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Notification {
+    /// The WebSocket connection to AuxProxy was lost. The SDK keeps trying to
+    /// reconnect in the background.
+    Disconnected,
+
+    /// The WebSocket connection to AuxProxy was restored after `attempts`
+    /// reconnection attempts. By this moment the SDK has already re-announced
+    /// ProcessReady and the current game session (if any).
+    Reconnected { attempts: u32 },
+
+    /// The connection was closed after ProcessEnding. The SDK does not
+    /// reconnect, since GameLift sends an ending process no more events.
+    ConnectionClosed,
+
+    /// The [process state](crate::api::Api::state) has changed.
+    StateChanged { from: crate::server_state::ProcessState, to: crate::server_state::ProcessState },
+
+    /// GameLift has asked the process to shut down, see
+    /// [get_termination_time](crate::api::Api::get_termination_time).
+    TerminationRequested { termination_time: Option<crate::entity::TerminationTimeType> },
+
+    /// The process has stopped accepting new players, see
+    /// [start_draining](crate::api::Api::start_draining).
+    Draining,
+
+    /// A game session has been activated, or has ended if `state` is `None`.
+    GameSessionStateChanged {
+        game_session_id: crate::entity::GameSessionId,
+        state: Option<crate::server_state::GameSessionState>,
+    },
+
+    /// The number of connected players of a game session has changed after a
+    /// player session was accepted or removed.
+    PlayerCountChanged { game_session_id: crate::entity::GameSessionId, player_count: usize },
+
+    /// A game session has ended while some of its player sessions were
+    /// accepted but never removed, i.e. GameLift still counts these players.
+    PlayerSessionsLeaked {
+        game_session_id: crate::entity::GameSessionId,
+        player_session_ids: Vec<crate::entity::PlayerSessionId>,
+    },
+
+    /// A match backfill ticket started with a
+    /// [BackfillManager](crate::backfill::BackfillManager) has changed its
+    /// state.
+    BackfillTicketUpdated {
+        game_session_id: crate::entity::GameSessionId,
+        ticket_id: crate::entity::TicketId,
+        state: crate::backfill::BackfillTicketState,
+    },
+}
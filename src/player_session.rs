@@ -0,0 +1,252 @@
+/// The state of a player session as seen by the server process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+pub enum PlayerSessionState {
+    /// GameLift has reserved a slot for the player, who has not connected yet.
+    /// Known from
+    /// [describe_player_sessions](crate::api::Api::describe_player_sessions).
+    Reserved,
+
+    /// The player session has been accepted and the player is connected.
+    Accepted,
+
+    /// The player session has been removed after the player disconnected.
+    Removed,
+}
+
+/// What the server process knows about a player session of one of its game
+/// sessions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerSessionRecord {
+    pub player_session_id: crate::entity::PlayerSessionId,
+    pub state: PlayerSessionState,
+
+    /// The player ID, if the player session has been described.
+    pub player_id: Option<crate::entity::PlayerId>,
+
+    /// Developer-defined player data, if the player session has been
+    /// described.
+    pub player_data: Option<String>,
+
+    /// When the reservation was first seen in a describe_player_sessions
+    /// result.
+    pub reserved_at: Option<std::time::SystemTime>,
+    pub accepted_at: Option<std::time::SystemTime>,
+    pub removed_at: Option<std::time::SystemTime>,
+}
+
+impl PlayerSessionRecord {
+    fn new(player_session_id: crate::entity::PlayerSessionId, state: PlayerSessionState) -> Self {
+        Self {
+            player_session_id,
+            state,
+            player_id: None,
+            player_data: None,
+            reserved_at: None,
+            accepted_at: None,
+            removed_at: None,
+        }
+    }
+}
+
+/// Player sessions of a single game session.
+#[derive(Default)]
+pub(crate) struct PlayerSessionRegistry {
+    records: std::collections::HashMap<crate::entity::PlayerSessionId, PlayerSessionRecord>,
+}
+
+impl PlayerSessionRegistry {
+    /// Merges a player session returned by describe_player_sessions.
+    pub fn record_description(&mut self, player_session: &crate::entity::PlayerSession) {
+        let player_session_id = match player_session.player_session_id.as_ref() {
+            Some(player_session_id) => player_session_id,
+            None => return,
+        };
+        let record = match self.records.get_mut(player_session_id) {
+            Some(record) => record,
+            None if player_session.status == crate::entity::PlayerSessionStatus::Reserved => {
+                let mut record = PlayerSessionRecord::new(
+                    player_session_id.clone(),
+                    PlayerSessionState::Reserved,
+                );
+                record.reserved_at = Some(std::time::SystemTime::now());
+                self.records.entry(player_session_id.clone()).or_insert(record)
+            }
+            // Player sessions that never reached this process are of no
+            // interest.
+            None => return,
+        };
+
+        if player_session.player_id.is_some() {
+            record.player_id = player_session.player_id.clone();
+        }
+        if player_session.player_data.is_some() {
+            record.player_data = player_session.player_data.clone();
+        }
+    }
+
+    /// Marks the player session accepted and returns the previous record to
+    /// [restore](Self::restore) if GameLift rejects the player session.
+    /// Fails if the player session has been accepted before.
+    pub fn accept(
+        &mut self,
+        player_session_id: &str,
+    ) -> Result<Option<PlayerSessionRecord>, crate::error::GameLiftErrorType> {
+        let previous = self.records.get(player_session_id).cloned();
+        let mut record = match previous.clone() {
+            Some(record) if record.state != PlayerSessionState::Reserved => {
+                return Err(crate::error::GameLiftErrorType::PlayerSessionAlreadyAccepted(
+                    player_session_id.to_string(),
+                ));
+            }
+            Some(record) => record,
+            None => PlayerSessionRecord::new(
+                player_session_id.to_string(),
+                PlayerSessionState::Reserved,
+            ),
+        };
+
+        record.state = PlayerSessionState::Accepted;
+        record.accepted_at = Some(std::time::SystemTime::now());
+        self.records.insert(player_session_id.to_string(), record);
+        Ok(previous)
+    }
+
+    /// Undoes an [accept](Self::accept).
+    pub fn restore(&mut self, player_session_id: &str, previous: Option<PlayerSessionRecord>) {
+        match previous {
+            Some(previous) => {
+                self.records.insert(player_session_id.to_string(), previous);
+            }
+            None => {
+                self.records.remove(player_session_id);
+            }
+        }
+    }
+
+    /// Marks a player session as removed. Unknown IDs are ignored, so that
+    /// they can still be accepted later.
+    pub fn remove(&mut self, player_session_id: &str) {
+        if let Some(record) = self.records.get_mut(player_session_id) {
+            record.state = PlayerSessionState::Removed;
+            record.removed_at = Some(std::time::SystemTime::now());
+        }
+    }
+
+    pub fn get(&self, player_session_id: &str) -> Option<PlayerSessionRecord> {
+        self.records.get(player_session_id).cloned()
+    }
+
+    pub fn records(&self) -> Vec<PlayerSessionRecord> {
+        self.records.values().cloned().collect()
+    }
+
+    /// Returns the number of connected players.
+    pub fn player_count(&self) -> usize {
+        self.records.values().filter(|record| record.state == PlayerSessionState::Accepted).count()
+    }
+
+    /// Returns the player sessions that have been accepted but never removed.
+    pub fn leaked(&self) -> Vec<crate::entity::PlayerSessionId> {
+        let mut leaked: Vec<_> = self
+            .records
+            .values()
+            .filter(|record| record.state == PlayerSessionState::Accepted)
+            .map(|record| record.player_session_id.clone())
+            .collect();
+        leaked.sort();
+        leaked
+    }
+}
+
+/// Page size of [describe_stream] if the request has no limit.
+pub const DEFAULT_PAGE_SIZE: i32 = 50;
+
+/// Describes the player sessions matching the request, following
+/// `next_token` until the last page. `request.limit` is the page size. The
+/// stream ends after the first error.
+pub(crate) fn describe_stream(
+    state: std::sync::Arc<crate::server_state::ServerState>,
+    mut request: crate::entity::DescribePlayerSessionsRequest,
+) -> impl futures_util::Stream<
+    Item = Result<crate::entity::PlayerSession, crate::error::GameLiftErrorType>,
+> + Send
+       + 'static {
+    if request.limit <= 0 {
+        request.limit = DEFAULT_PAGE_SIZE;
+    }
+
+    let page = std::collections::VecDeque::new();
+    futures_util::stream::unfold(
+        (state, Some(request), page),
+        |(state, mut next_request, mut page)| async move {
+            loop {
+                if let Some(player_session) = page.pop_front() {
+                    return Some((Ok(player_session), (state, next_request, page)));
+                }
+
+                let request = next_request.take()?;
+                match state.describe_player_sessions(request.clone()).await {
+                    Ok(result) => {
+                        page.extend(result.player_sessions);
+                        // The token is ignored if a player session ID is given.
+                        if !result.next_token.is_empty() && request.player_session_id.is_none() {
+                            next_request = Some(crate::entity::DescribePlayerSessionsRequest {
+                                next_token: Some(result.next_token),
+                                ..request
+                            });
+                        }
+                    }
+                    Err(error) => return Some((Err(error), (state, None, page))),
+                }
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::player_session::{PlayerSessionRegistry, PlayerSessionState};
+
+    #[test]
+    fn player_session_registry_test() {
+        let mut registry = PlayerSessionRegistry::default();
+        registry.record_description(&crate::entity::PlayerSession {
+            player_id: Some("player-1".to_string()),
+            player_session_id: Some("psess-1".to_string()),
+            game_session_id: Some("gsess-1".to_string()),
+            fleet_id: None,
+            ip_address: None,
+            player_data: Some("{\"skill\":10}".to_string()),
+            port: 14000,
+            creation_time: 0,
+            termination_time: 0,
+            status: crate::entity::PlayerSessionStatus::Reserved,
+            dns_name: None,
+        });
+        assert_eq!(registry.get("psess-1").unwrap().state, PlayerSessionState::Reserved);
+
+        let previous = registry.accept("psess-1").unwrap();
+        registry.accept("psess-2").unwrap();
+        assert!(matches!(
+            registry.accept("psess-1"),
+            Err(crate::error::GameLiftErrorType::PlayerSessionAlreadyAccepted(id)) if id == "psess-1"
+        ));
+        assert_eq!(registry.player_count(), 2);
+
+        registry.restore("psess-1", previous);
+        let record = registry.get("psess-1").unwrap();
+        assert_eq!(record.state, PlayerSessionState::Reserved);
+        assert_eq!(record.player_data.as_deref(), Some("{\"skill\":10}"));
+
+        registry.accept("psess-1").unwrap();
+        registry.remove("psess-2");
+        assert_eq!(registry.player_count(), 1);
+        assert_eq!(registry.leaked(), vec!["psess-1".to_string()]);
+        assert!(registry.accept("psess-2").is_err());
+
+        registry.remove("psess-3");
+        assert!(registry.get("psess-3").is_none());
+        registry.accept("psess-3").unwrap();
+        assert_eq!(registry.player_count(), 2);
+    }
+}
@@ -22,6 +22,10 @@ pub type OnHealthCheckType =
 
 /// This data type contains the set of parameters sent to the GameLift service
 /// in a [ProcessReady](crate::api::Api::process_ready) call.
+/// The callbacks run on separate tasks, see
+/// [callback_ordering](crate::sdk_config::SdkConfig::callback_ordering) for
+/// their ordering guarantees. To receive the events as a stream instead, use
+/// [process_ready_with_events](crate::api::Api::process_ready_with_events).
 pub struct ProcessParameters {
     /// Name of callback function that the GameLift service invokes to activate
     /// a new game session. GameLift calls this function in response to the
@@ -46,7 +50,10 @@ pub struct ProcessParameters {
     /// health status report from the server process. GameLift calls this
     /// function every 60 seconds. After calling this function GameLift waits 60
     /// seconds for a response, and if none is received. records the server
-    /// process as unhealthy.
+    /// process as unhealthy. The interval and the timeout are set in
+    /// [health_check](crate::sdk_config::SdkConfig::health_check), and further
+    /// checks can be added to the
+    /// [health registry](crate::api::Api::health_registry).
     pub on_health_check: Box<OnHealthCheckType>,
 
     /// Port number the server process will listen on for new player
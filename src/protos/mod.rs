@@ -1,5 +1,8 @@
 //! Generated files are imported from here.
 
+// The code generated by protobuf-codegen-pure 2 trips these lints of newer
+// compilers. `unknown_lints` keeps older compilers quiet about the newer ones.
+#[allow(mismatched_lifetime_syntaxes, renamed_and_removed_lints, unknown_lints, unused_parens)]
 pub mod generated_with_pure {
     include!(concat!(env!("OUT_DIR"), "/generated_with_pure/mod.rs"));
 }
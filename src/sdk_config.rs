@@ -0,0 +1,256 @@
+/// Host AuxProxy listens on. Overrides [SdkConfig::host].
+pub const HOST_ENV: &str = "GAMELIFT_SDK_HOST";
+/// AuxProxy HTTP port. Overrides [SdkConfig::http_port].
+pub const HTTP_PORT_ENV: &str = "GAMELIFT_SDK_HTTP_PORT";
+/// AuxProxy WebSocket port. Overrides [SdkConfig::web_socket_port].
+pub const WEB_SOCKET_PORT_ENV: &str = "GAMELIFT_SDK_WEBSOCKET_PORT";
+/// Request timeout in milliseconds. Overrides [SdkConfig::request_timeout].
+pub const REQUEST_TIMEOUT_ENV: &str = "GAMELIFT_SDK_REQUEST_TIMEOUT_MS";
+/// Connect timeout in milliseconds. Overrides [SdkConfig::connect_timeout].
+pub const CONNECT_TIMEOUT_ENV: &str = "GAMELIFT_SDK_CONNECT_TIMEOUT_MS";
+/// Process ID reported to AuxProxy. Overrides [SdkConfig::pid].
+pub const PID_ENV: &str = "GAMELIFT_SDK_PID";
+
+/// The protocol the SDK uses to talk to GameLift.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Protocol {
+    /// The Server SDK 4.x protocol: requests over HTTP and events over a
+    /// WebSocket connection to the local AuxProxy.
+    AuxProxy,
+
+    /// The Server SDK 5.x protocol: a single authenticated WebSocket
+    /// connection to GameLift, required by Anywhere fleets and containers.
+    /// Server SDK 5.x has no TerminateGameSession, so
+    /// [terminate_game_session](crate::api::Api::terminate_game_session) and
+    /// [GameSessionHandle::terminate](crate::game_session::GameSessionHandle::terminate)
+    /// fail with
+    /// [UnsupportedOperation](crate::error::GameLiftErrorType::UnsupportedOperation);
+    /// end the process with
+    /// [process_ending](crate::api::Api::process_ending) instead.
+    WebSocket(crate::server_parameters::ServerParameters),
+}
+
+/// Which [ProcessParameters](crate::process_parameters::ProcessParameters)
+/// callbacks may run at the same time. Every callback runs on its own task, so
+/// a slow callback never stops the SDK from receiving events.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CallbackOrdering {
+    /// One callback at a time, in the order GameLift sent the events.
+    Sequential,
+
+    /// Callbacks of the same kind run one at a time, in order, while
+    /// callbacks of different kinds may overlap, e.g. `on_process_terminate`
+    /// is not delayed by a slow `on_start_game_session`.
+    #[default]
+    PerKind,
+
+    /// Every callback starts as soon as its event arrives.
+    Unordered,
+}
+
+/// Timing of the health reports the SDK sends to GameLift, see
+/// [HealthRegistry](crate::health::HealthRegistry).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthCheckConfig {
+    /// Time between two health reports.
+    pub interval: std::time::Duration,
+
+    /// Time the health checks and `on_health_check` have to answer. A check
+    /// that does not answer in time is unhealthy.
+    pub timeout: std::time::Duration,
+
+    /// Upper bound of a random delay added to every interval, so that the
+    /// processes of an instance do not all check their health at once.
+    pub jitter: std::time::Duration,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            interval: std::time::Duration::from_secs(60),
+            timeout: std::time::Duration::from_secs(60),
+            jitter: std::time::Duration::ZERO,
+        }
+    }
+}
+
+/// This data type contains the settings the SDK uses to reach AuxProxy. The
+/// defaults match an AuxProxy running on the same host, which is the case on
+/// GameLift fleets. Pass it to [Api::new](crate::api::Api::new).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SdkConfig {
+    /// The protocol to use. The connection settings below apply to
+    /// [Protocol::AuxProxy] only, except for the timeouts.
+    pub protocol: Protocol,
+
+    /// Host name or IP address AuxProxy listens on.
+    pub host: String,
+
+    /// Port of the AuxProxy HTTP endpoint the SDK sends requests to.
+    pub http_port: u16,
+
+    /// Port of the AuxProxy WebSocket endpoint the SDK receives events from.
+    pub web_socket_port: u16,
+
+    /// Timeout of a single request to AuxProxy. `None` means no timeout.
+    pub request_timeout: Option<std::time::Duration>,
+
+    /// Timeout of establishing a connection to AuxProxy. `None` means no
+    /// timeout.
+    pub connect_timeout: Option<std::time::Duration>,
+
+    /// Process ID AuxProxy uses to identify this server process. It must be
+    /// the ID of the process GameLift launched, so override it only if the
+    /// game server runs in a child process or a different PID namespace.
+    pub pid: u32,
+
+    /// Ordering guarantees of the callbacks passed to
+    /// [process_ready](crate::api::Api::process_ready).
+    pub callback_ordering: CallbackOrdering,
+
+    /// Time after which a callback is cancelled. `None` means no timeout. An
+    /// `on_health_check` callback that does not finish in time reports the
+    /// process as unhealthy.
+    pub callback_timeout: Option<std::time::Duration>,
+
+    /// Number of game sessions the process can host at the same time, sent
+    /// to GameLift in ProcessReady. With more than one, use
+    /// [GameSessionHandle](crate::game_session::GameSessionHandle)s to target
+    /// a particular game session.
+    pub max_concurrent_game_sessions: u32,
+
+    /// Timing of the health reports.
+    pub health_check: HealthCheckConfig,
+}
+
+impl Default for SdkConfig {
+    fn default() -> Self {
+        Self {
+            protocol: Protocol::AuxProxy,
+            host: "127.0.0.1".to_string(),
+            http_port: 5758,
+            web_socket_port: 5759,
+            request_timeout: None,
+            connect_timeout: None,
+            pid: std::process::id(),
+            callback_ordering: Default::default(),
+            callback_timeout: None,
+            max_concurrent_game_sessions: 1,
+            health_check: Default::default(),
+        }
+    }
+}
+
+impl SdkConfig {
+    /// Returns the default configuration with the environment overrides
+    /// applied.
+    pub fn from_env() -> Self {
+        Self::default().with_env_overrides()
+    }
+
+    /// Overrides the settings with the values of the `GAMELIFT_SDK_*`
+    /// environment variables that are set. Invalid values are logged and
+    /// ignored. The protocol is never changed: to use the Server SDK 5.x
+    /// protocol, select [Protocol::WebSocket] explicitly, e.g. with
+    /// [ServerParameters::from_env](crate::server_parameters::ServerParameters::from_env).
+    pub fn with_env_overrides(self) -> Self {
+        self.with_overrides(|name| std::env::var(name).ok())
+    }
+
+    fn with_overrides(mut self, get: impl Fn(&str) -> Option<String>) -> Self {
+        if let Some(host) = get(HOST_ENV) {
+            if reqwest::Url::parse(&format!("http://{}/", uri_host(&host))).is_ok() {
+                self.host = host;
+            } else {
+                log::warn!("Ignoring invalid value of {}: {}", HOST_ENV, host);
+            }
+        }
+        if let Some(http_port) = parse_override(&get, HTTP_PORT_ENV) {
+            self.http_port = http_port;
+        }
+        if let Some(web_socket_port) = parse_override(&get, WEB_SOCKET_PORT_ENV) {
+            self.web_socket_port = web_socket_port;
+        }
+        if let Some(request_timeout) = parse_override(&get, REQUEST_TIMEOUT_ENV) {
+            self.request_timeout = Some(std::time::Duration::from_millis(request_timeout));
+        }
+        if let Some(connect_timeout) = parse_override(&get, CONNECT_TIMEOUT_ENV) {
+            self.connect_timeout = Some(std::time::Duration::from_millis(connect_timeout));
+        }
+        if let Some(pid) = parse_override(&get, PID_ENV) {
+            self.pid = pid;
+        }
+
+        self
+    }
+
+    pub(crate) fn http_uri(&self) -> String {
+        format!("http://{}:{}/", uri_host(&self.host), self.http_port)
+    }
+
+    pub(crate) fn web_socket_uri(&self) -> String {
+        format!("ws://{}:{}/", uri_host(&self.host), self.web_socket_port)
+    }
+}
+
+/// Brackets IPv6 addresses, which would otherwise clash with the port
+/// separator.
+fn uri_host(host: &str) -> std::borrow::Cow<'_, str> {
+    match host.parse::<std::net::Ipv6Addr>() {
+        Ok(_) => format!("[{}]", host).into(),
+        Err(_) => host.into(),
+    }
+}
+
+fn parse_override<T>(get: impl Fn(&str) -> Option<String>, name: &str) -> Option<T>
+where
+    T: std::str::FromStr,
+{
+    let value = get(name)?;
+    match value.parse() {
+        Ok(value) => Some(value),
+        Err(_) => {
+            log::warn!("Ignoring invalid value of {}: {}", name, value);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sdk_config::{
+        SdkConfig, HOST_ENV, HTTP_PORT_ENV, REQUEST_TIMEOUT_ENV, WEB_SOCKET_PORT_ENV,
+    };
+
+    #[test]
+    fn with_overrides_test() {
+        let config = SdkConfig::default().with_overrides(|name| match name {
+            HTTP_PORT_ENV => Some("15758".to_string()),
+            WEB_SOCKET_PORT_ENV => Some("not a port".to_string()),
+            REQUEST_TIMEOUT_ENV => Some("1500".to_string()),
+            _ => None,
+        });
+
+        assert_eq!(config.http_port, 15758);
+        assert_eq!(config.web_socket_port, 5759);
+        assert_eq!(config.request_timeout, Some(std::time::Duration::from_millis(1500)));
+        assert_eq!(config.http_uri(), "http://127.0.0.1:15758/");
+    }
+
+    #[test]
+    fn host_override_test() {
+        let config = SdkConfig::default().with_overrides(|name| match name {
+            HOST_ENV => Some("::1".to_string()),
+            _ => None,
+        });
+        assert_eq!(config.host, "::1");
+        assert_eq!(config.http_uri(), "http://[::1]:5758/");
+        assert_eq!(config.web_socket_uri(), "ws://[::1]:5759/");
+
+        let config = SdkConfig::default().with_overrides(|name| match name {
+            HOST_ENV => Some("not a host/".to_string()),
+            _ => None,
+        });
+        assert_eq!(config.host, "127.0.0.1");
+    }
+}
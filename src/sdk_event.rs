@@ -0,0 +1,78 @@
+/// Events GameLift sends to the server process. They are an alternative to the
+/// [ProcessParameters](crate::process_parameters::ProcessParameters)
+/// callbacks, see
+/// [process_ready_with_events](crate::api::Api::process_ready_with_events).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SdkEvent {
+    /// GameLift asks the server process to start a game session. Call
+    /// [activate_game_session](crate::api::Api::activate_game_session) once
+    /// the game session is ready to accept players.
+    StartGameSession(crate::entity::GameSession),
+
+    /// GameLift passes an updated game session, e.g. after a match backfill
+    /// request has been processed.
+    UpdateGameSession(crate::entity::UpdateGameSession),
+
+    /// GameLift asks the server process to shut down. The termination time is
+    /// also available via
+    /// [get_termination_time](crate::api::Api::get_termination_time).
+    ProcessTerminate { termination_time: Option<crate::entity::TerminationTimeType> },
+
+    /// GameLift requests a health status report. The process is reported
+    /// unhealthy if the responder is dropped or not answered within the
+    /// [health check timeout](crate::sdk_config::HealthCheckConfig::timeout).
+    HealthCheck(HealthCheckResponder),
+}
+
+/// Answers an [SdkEvent::HealthCheck].
+#[derive(Debug)]
+pub struct HealthCheckResponder {
+    sender: tokio::sync::oneshot::Sender<bool>,
+}
+
+impl HealthCheckResponder {
+    pub(crate) fn new() -> (Self, tokio::sync::oneshot::Receiver<bool>) {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        (Self { sender }, receiver)
+    }
+
+    /// Reports whether the server process is healthy.
+    pub fn respond(self, is_healthy: bool) {
+        // The health check may have timed out already.
+        let _ = self.sender.send(is_healthy);
+    }
+}
+
+/// A stream of [SdkEvent]s. It ends when the SDK is destroyed.
+#[derive(Debug)]
+pub struct SdkEvents {
+    receiver: tokio::sync::mpsc::Receiver<SdkEvent>,
+}
+
+impl SdkEvents {
+    pub(crate) fn new(receiver: tokio::sync::mpsc::Receiver<SdkEvent>) -> Self {
+        Self { receiver }
+    }
+
+    /// Receives the next event. Returns `None` when the SDK is destroyed.
+    pub async fn recv(&mut self) -> Option<SdkEvent> {
+        self.receiver.recv().await
+    }
+
+    /// Returns the underlying channel receiver.
+    pub fn into_inner(self) -> tokio::sync::mpsc::Receiver<SdkEvent> {
+        self.receiver
+    }
+}
+
+impl futures_util::Stream for SdkEvents {
+    type Item = SdkEvent;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
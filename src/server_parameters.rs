@@ -0,0 +1,63 @@
+/// URL of the GameLift WebSocket endpoint. Read by
+/// [ServerParameters::from_env].
+pub const WEB_SOCKET_URL_ENV: &str = "GAMELIFT_SDK_WEBSOCKET_URL";
+/// Process ID used by GameLift. Read by [ServerParameters::from_env].
+pub const PROCESS_ID_ENV: &str = "GAMELIFT_SDK_PROCESS_ID";
+/// Compute (host) ID. Read by [ServerParameters::from_env].
+pub const HOST_ID_ENV: &str = "GAMELIFT_SDK_HOST_ID";
+/// Fleet ID. Read by [ServerParameters::from_env].
+pub const FLEET_ID_ENV: &str = "GAMELIFT_SDK_FLEET_ID";
+/// Authentication token. Read by [ServerParameters::from_env].
+pub const AUTH_TOKEN_ENV: &str = "GAMELIFT_SDK_AUTH_TOKEN";
+
+/// This data type contains the set of parameters used to connect a server
+/// process to GameLift with the Server SDK 5.x protocol, e.g. on GameLift
+/// Anywhere fleets. On managed EC2 fleets GameLift provides the values
+/// through environment variables, see [from_env](Self::from_env).
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct ServerParameters {
+    /// The URL of the GameLift WebSocket endpoint, returned by the
+    /// RegisterCompute API for Anywhere fleets.
+    pub web_socket_url: String,
+
+    /// A unique identifier of the server process.
+    pub process_id: String,
+
+    /// The ID of the compute hosting the server process.
+    pub host_id: String,
+
+    /// The ID of the fleet the compute is registered to.
+    pub fleet_id: String,
+
+    /// The authentication token generated by the GetComputeAuthToken API.
+    pub auth_token: String,
+}
+
+impl std::fmt::Debug for ServerParameters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServerParameters")
+            .field("web_socket_url", &self.web_socket_url)
+            .field("process_id", &self.process_id)
+            .field("host_id", &self.host_id)
+            .field("fleet_id", &self.fleet_id)
+            .field("auth_token", &"<redacted>")
+            .finish()
+    }
+}
+
+impl ServerParameters {
+    /// Reads the parameters from the `GAMELIFT_SDK_*` environment variables
+    /// GameLift sets for server processes on managed EC2 fleets. Returns
+    /// `None` if the WebSocket URL is not set.
+    pub fn from_env() -> Option<Self> {
+        let var = |name| std::env::var(name).unwrap_or_default();
+
+        Some(Self {
+            web_socket_url: std::env::var(WEB_SOCKET_URL_ENV).ok()?,
+            process_id: var(PROCESS_ID_ENV),
+            host_id: var(HOST_ID_ENV),
+            fleet_id: var(FLEET_ID_ENV),
+            auth_token: var(AUTH_TOKEN_ENV),
+        })
+    }
+}
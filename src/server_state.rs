@@ -1,53 +1,701 @@
 use crate::{entity::GetInstanceCertificateResult, error::GameLiftErrorType};
 use tokio::task::JoinHandle;
 
-const HEALTHCHECK_TIMEOUT_SECONDS: u64 = 60;
+const NOTIFICATION_CHANNEL_CAPACITY: usize = 16;
+const EVENT_CHANNEL_CAPACITY: usize = 16;
+
+/// The lifecycle state of the server process and its game session, see
+/// [Api::state](crate::api::Api::state). Calls that are not valid in the
+/// current state fail with
+/// [IllegalState](crate::error::GameLiftErrorType::IllegalState).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+pub enum ProcessState {
+    /// [init_sdk](crate::api::Api::init_sdk) has not succeeded yet.
+    #[default]
+    NotInitialized,
+
+    /// The SDK is initialized, but ProcessReady has not been sent yet.
+    Initialized,
+
+    /// The process is ready to host a game session.
+    Ready,
+
+    /// GameLift has started a game session, which is not activated yet.
+    SessionStarting,
+
+    /// The game session is activated and accepts players.
+    SessionActive,
+
+    /// ProcessEnding is being sent to GameLift.
+    Ending,
+
+    /// The process has ended, GameLift no longer sends it any events.
+    Ended,
+}
+
+impl ProcessState {
+    /// Whether GameLift considers the process ready, i.e. it sends events and
+    /// expects health reports.
+    pub fn is_process_ready(self) -> bool {
+        READY_STATES.contains(&self)
+    }
+}
+
+const READY_STATES: &[ProcessState] =
+    &[ProcessState::Ready, ProcessState::SessionStarting, ProcessState::SessionActive];
+
+/// The state of a game session hosted by the process, see
+/// [GameSessionHandle](crate::game_session::GameSessionHandle).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+pub enum GameSessionState {
+    /// GameLift has started the game session, which is not activated yet.
+    Starting,
+
+    /// The game session is activated and accepts players.
+    Active,
+}
+
+struct GameSessionEntry {
+    state: GameSessionState,
+    game_session: crate::entity::GameSession,
+    player_sessions: crate::player_session::PlayerSessionRegistry,
+    backfill_tickets: crate::backfill::BackfillTickets,
+}
 
 #[derive(Default)]
 struct SessionState {
-    is_process_ready: bool,
-    game_session_id: Option<crate::entity::GameSessionId>,
+    state: ProcessState,
+    game_sessions: std::collections::HashMap<crate::entity::GameSessionId, GameSessionEntry>,
     termination_time: Option<crate::entity::TerminationTimeType>,
+    draining: bool,
+}
+
+impl SessionState {
+    /// The state of a ready process hosting the current game sessions.
+    fn ready_state(&self) -> ProcessState {
+        let mut game_session_states = self.game_sessions.values().map(|entry| entry.state);
+        if game_session_states.clone().any(|state| state == GameSessionState::Active) {
+            ProcessState::SessionActive
+        } else if game_session_states.next().is_some() {
+            ProcessState::SessionStarting
+        } else {
+            ProcessState::Ready
+        }
+    }
+}
+
+/// What the process announced in ProcessReady and where its events go.
+#[derive(Clone)]
+struct ProcessRegistration {
+    port: i32,
+    log_paths: Vec<String>,
+    event_sender: tokio::sync::mpsc::Sender<crate::sdk_event::SdkEvent>,
 }
 
-#[derive(Default)]
 pub struct ServerStateInner {
-    process_parameters: Option<crate::process_parameters::ProcessParameters>,
+    process_registration: parking_lot::RwLock<Option<ProcessRegistration>>,
     session_state: parking_lot::RwLock<SessionState>,
-    http_client: crate::http_client::HttpClient,
+    transport: crate::transport::Transport,
+    notification_sender: tokio::sync::broadcast::Sender<crate::notification::Notification>,
+    max_concurrent_game_sessions: usize,
+    health_check: crate::sdk_config::HealthCheckConfig,
+    health_registry: crate::health::HealthRegistry,
 }
 
 impl ServerStateInner {
+    pub fn new(config: &crate::sdk_config::SdkConfig) -> Self {
+        let (notification_sender, _) =
+            tokio::sync::broadcast::channel(NOTIFICATION_CHANNEL_CAPACITY);
+
+        Self {
+            process_registration: Default::default(),
+            session_state: Default::default(),
+            transport: crate::transport::Transport::new(config),
+            notification_sender,
+            max_concurrent_game_sessions: config.max_concurrent_game_sessions.max(1) as usize,
+            health_check: config.health_check,
+            health_registry: Default::default(),
+        }
+    }
+
+    pub fn state(&self) -> ProcessState {
+        self.session_state.read().state
+    }
+
+    pub fn sdk_version(&self) -> &'static str {
+        self.transport.sdk_version()
+    }
+
     pub fn is_process_ready(&self) -> bool {
-        self.session_state.read().is_process_ready
+        self.state().is_process_ready()
+    }
+
+    /// Whether ProcessEnding is being sent or has been sent.
+    pub fn is_process_ending(&self) -> bool {
+        matches!(self.state(), ProcessState::Ending | ProcessState::Ended)
+    }
+
+    /// Fails if `operation` is not allowed in the current state.
+    fn check_state(
+        &self,
+        operation: &'static str,
+        allowed: &[ProcessState],
+    ) -> Result<(), GameLiftErrorType> {
+        let state = self.state();
+        if allowed.contains(&state) {
+            Ok(())
+        } else {
+            Err(GameLiftErrorType::IllegalState { operation, state })
+        }
+    }
+
+    /// Moves to the `to` state if `operation` is allowed in the current state.
+    fn transition(
+        &self,
+        operation: &'static str,
+        allowed: &[ProcessState],
+        to: ProcessState,
+    ) -> Result<(), GameLiftErrorType> {
+        let from = {
+            let mut session_state = self.session_state.write();
+            let from = session_state.state;
+            if !allowed.contains(&from) {
+                return Err(GameLiftErrorType::IllegalState { operation, state: from });
+            }
+            session_state.state = to;
+            from
+        };
+
+        self.on_state_changed(from, to);
+        Ok(())
+    }
+
+    /// Applies `update` to the game sessions of a ready process and moves to
+    /// the state implied by the result.
+    fn update_game_sessions<T>(
+        &self,
+        operation: &'static str,
+        update: impl FnOnce(
+            &mut std::collections::HashMap<crate::entity::GameSessionId, GameSessionEntry>,
+        ) -> Result<T, GameLiftErrorType>,
+    ) -> Result<T, GameLiftErrorType> {
+        let (from, to, result) = {
+            let mut session_state = self.session_state.write();
+            let from = session_state.state;
+            if !from.is_process_ready() {
+                return Err(GameLiftErrorType::IllegalState { operation, state: from });
+            }
+            let result = update(&mut session_state.game_sessions)
+                .map_err(|error| Self::with_state(error, from))?;
+            session_state.state = session_state.ready_state();
+            (from, session_state.state, result)
+        };
+
+        self.on_state_changed(from, to);
+        Ok(result)
+    }
+
+    /// Fills in the process state of an
+    /// [IllegalState](GameLiftErrorType::IllegalState) error.
+    fn with_state(error: GameLiftErrorType, state: ProcessState) -> GameLiftErrorType {
+        match error {
+            GameLiftErrorType::IllegalState { operation, .. } => {
+                GameLiftErrorType::IllegalState { operation, state }
+            }
+            error => error,
+        }
+    }
+
+    /// Moves the game session from one of the `allowed` states to `to`, or
+    /// forgets it if `to` is `None`.
+    fn update_game_session(
+        &self,
+        operation: &'static str,
+        game_session_id: &str,
+        allowed: &[GameSessionState],
+        to: Option<GameSessionState>,
+    ) -> Result<(), GameLiftErrorType> {
+        let ended_game_session = self.update_game_sessions(operation, |game_sessions| {
+            Self::check_game_session_entry(operation, game_sessions, game_session_id, allowed)?;
+            Ok(match to {
+                Some(to) => {
+                    if let Some(entry) = game_sessions.get_mut(game_session_id) {
+                        entry.state = to;
+                    }
+                    None
+                }
+                None => game_sessions.remove(game_session_id),
+            })
+        })?;
+
+        if let Some(entry) = ended_game_session {
+            self.report_leaked_player_sessions(game_session_id, &entry);
+        }
+        self.notify(crate::notification::Notification::GameSessionStateChanged {
+            game_session_id: game_session_id.to_string(),
+            state: to,
+        });
+        Ok(())
+    }
+
+    /// Applies `update` to a hosted game session.
+    fn update_game_session_entry<T>(
+        &self,
+        operation: &'static str,
+        game_session_id: &str,
+        allowed: &[GameSessionState],
+        update: impl FnOnce(&mut GameSessionEntry) -> Result<T, GameLiftErrorType>,
+    ) -> Result<T, GameLiftErrorType> {
+        let mut session_state = self.session_state.write();
+        let state = session_state.state;
+        Self::check_game_session_entry(
+            operation,
+            &session_state.game_sessions,
+            game_session_id,
+            allowed,
+        )
+        .map_err(|error| Self::with_state(error, state))?;
+        match session_state.game_sessions.get_mut(game_session_id) {
+            Some(entry) => update(entry),
+            None => Err(GameLiftErrorType::GameSessionNotFound(game_session_id.to_string())),
+        }
+    }
+
+    /// Applies `update` to the player sessions of a hosted game session.
+    fn update_player_sessions<T>(
+        &self,
+        operation: &'static str,
+        game_session_id: &str,
+        allowed: &[GameSessionState],
+        update: impl FnOnce(
+            &mut crate::player_session::PlayerSessionRegistry,
+        ) -> Result<T, GameLiftErrorType>,
+    ) -> Result<T, GameLiftErrorType> {
+        self.update_game_session_entry(operation, game_session_id, allowed, |entry| {
+            update(&mut entry.player_sessions)
+        })
+    }
+
+    /// Applies `update` to the backfill tickets of a hosted game session.
+    fn update_backfill_tickets<T>(
+        &self,
+        operation: &'static str,
+        game_session_id: &str,
+        update: impl FnOnce(&mut crate::backfill::BackfillTickets) -> Result<T, GameLiftErrorType>,
+    ) -> Result<T, GameLiftErrorType> {
+        self.update_game_session_entry(
+            operation,
+            game_session_id,
+            &[GameSessionState::Starting, GameSessionState::Active],
+            |entry| update(&mut entry.backfill_tickets),
+        )
     }
 
-    pub fn get_game_session_id(&self) -> Option<crate::entity::GameSessionId> {
-        self.session_state.read().game_session_id.clone()
+    /// Starts tracking a backfill ticket of a hosted game session and returns
+    /// the request to send for it.
+    fn start_backfill_ticket(
+        &self,
+        operation: &'static str,
+        game_session_id: &str,
+    ) -> Result<
+        (crate::backfill::BackfillTicket, crate::entity::StartMatchBackfillRequest),
+        GameLiftErrorType,
+    > {
+        self.update_game_session_entry(
+            operation,
+            game_session_id,
+            &[GameSessionState::Starting, GameSessionState::Active],
+            |entry| {
+                let matchmaker_data = entry
+                    .game_session
+                    .matchmaker_data()?
+                    .ok_or(GameLiftErrorType::MatchmakerDataNotSet)?;
+                let ticket_id = crate::backfill::new_ticket_id();
+                entry.backfill_tickets.start(ticket_id.clone())?;
+                let request = crate::entity::StartMatchBackfillRequest {
+                    ticket_id: Some(ticket_id.clone()),
+                    game_session_arn: Some(game_session_id.to_string()),
+                    players: Some(crate::backfill::backfill_players(
+                        &matchmaker_data,
+                        &entry.player_sessions,
+                    )),
+                    matchmaking_configuration_arn: Some(
+                        matchmaker_data.matchmaking_configuration_arn,
+                    ),
+                };
+                let ticket = entry.backfill_tickets.get(&ticket_id).expect("The ticket is tracked");
+                Ok((ticket, request))
+            },
+        )
+    }
+
+    /// Moves a pending backfill ticket to its final state and notifies about
+    /// it.
+    fn finish_backfill_ticket(
+        &self,
+        game_session_id: &str,
+        ticket_id: &str,
+        state: crate::backfill::BackfillTicketState,
+    ) {
+        let finished = self
+            .session_state
+            .write()
+            .game_sessions
+            .get_mut(game_session_id)
+            .is_some_and(|entry| entry.backfill_tickets.finish(ticket_id, state));
+        if finished {
+            log::debug!("The backfill ticket {} is {}.", ticket_id, state);
+            self.notify(crate::notification::Notification::BackfillTicketUpdated {
+                game_session_id: game_session_id.to_string(),
+                ticket_id: ticket_id.to_string(),
+                state,
+            });
+        }
+    }
+
+    pub fn backfill_tickets(&self, game_session_id: &str) -> Vec<crate::backfill::BackfillTicket> {
+        self.session_state
+            .read()
+            .game_sessions
+            .get(game_session_id)
+            .map(|entry| entry.backfill_tickets.tickets())
+            .unwrap_or_default()
+    }
+
+    pub fn backfill_ticket(
+        &self,
+        game_session_id: &str,
+        ticket_id: &str,
+    ) -> Option<crate::backfill::BackfillTicket> {
+        self.session_state
+            .read()
+            .game_sessions
+            .get(game_session_id)
+            .and_then(|entry| entry.backfill_tickets.get(ticket_id))
+    }
+
+    pub fn active_backfill_ticket(
+        &self,
+        game_session_id: &str,
+    ) -> Option<crate::backfill::BackfillTicket> {
+        self.session_state
+            .read()
+            .game_sessions
+            .get(game_session_id)
+            .and_then(|entry| entry.backfill_tickets.active())
+    }
+
+    pub fn player_sessions(
+        &self,
+        game_session_id: &str,
+    ) -> Vec<crate::player_session::PlayerSessionRecord> {
+        self.session_state
+            .read()
+            .game_sessions
+            .get(game_session_id)
+            .map(|entry| entry.player_sessions.records())
+            .unwrap_or_default()
+    }
+
+    pub fn player_session(
+        &self,
+        game_session_id: &str,
+        player_session_id: &str,
+    ) -> Option<crate::player_session::PlayerSessionRecord> {
+        self.session_state
+            .read()
+            .game_sessions
+            .get(game_session_id)
+            .and_then(|entry| entry.player_sessions.get(player_session_id))
+    }
+
+    /// Returns the number of connected players in the given game session, or
+    /// in all of them.
+    pub fn player_count(&self, game_session_id: Option<&str>) -> usize {
+        self.session_state
+            .read()
+            .game_sessions
+            .iter()
+            .filter(|(id, _)| game_session_id.is_none_or(|game_session_id| *id == game_session_id))
+            .map(|(_, entry)| entry.player_sessions.player_count())
+            .sum()
+    }
+
+    fn on_player_count_changed(&self, game_session_id: &str) {
+        self.notify(crate::notification::Notification::PlayerCountChanged {
+            game_session_id: game_session_id.to_string(),
+            player_count: self.player_count(Some(game_session_id)),
+        });
+    }
+
+    /// Merges the described player sessions of the hosted game sessions into
+    /// their registries.
+    fn record_player_sessions(&self, player_sessions: &[crate::entity::PlayerSession]) {
+        let mut session_state = self.session_state.write();
+        for player_session in player_sessions {
+            if let Some(entry) = player_session
+                .game_session_id
+                .as_ref()
+                .and_then(|game_session_id| session_state.game_sessions.get_mut(game_session_id))
+            {
+                entry.player_sessions.record_description(player_session);
+            }
+        }
+    }
+
+    /// Warns about player sessions of an ended game session that have been
+    /// accepted but never removed.
+    fn report_leaked_player_sessions(&self, game_session_id: &str, entry: &GameSessionEntry) {
+        let player_session_ids = entry.player_sessions.leaked();
+        if player_session_ids.is_empty() {
+            return;
+        }
+
+        log::warn!(
+            "The game session {} has ended with player sessions that were never removed: {:?}",
+            game_session_id,
+            player_session_ids
+        );
+        self.notify(crate::notification::Notification::PlayerSessionsLeaked {
+            game_session_id: game_session_id.to_string(),
+            player_session_ids,
+        });
+    }
+
+    /// Reports leaked player sessions of all game sessions, which end together
+    /// with the process.
+    fn on_process_ended(&self) {
+        let session_state = self.session_state.read();
+        for (game_session_id, entry) in &session_state.game_sessions {
+            self.report_leaked_player_sessions(game_session_id, entry);
+        }
+    }
+
+    /// Fails if the game session is unknown or `operation` is not allowed in
+    /// its state.
+    fn check_game_session(
+        &self,
+        operation: &'static str,
+        game_session_id: &str,
+        allowed: &[GameSessionState],
+    ) -> Result<(), GameLiftErrorType> {
+        let session_state = self.session_state.read();
+        Self::check_game_session_entry(
+            operation,
+            &session_state.game_sessions,
+            game_session_id,
+            allowed,
+        )
+        .map_err(|error| Self::with_state(error, session_state.state))
+    }
+
+    fn check_game_session_entry(
+        operation: &'static str,
+        game_sessions: &std::collections::HashMap<crate::entity::GameSessionId, GameSessionEntry>,
+        game_session_id: &str,
+        allowed: &[GameSessionState],
+    ) -> Result<(), GameLiftErrorType> {
+        match game_sessions.get(game_session_id) {
+            None => Err(GameLiftErrorType::GameSessionNotFound(game_session_id.to_string())),
+            Some(entry) if !allowed.contains(&entry.state) => {
+                // The process state is filled in by the caller.
+                Err(GameLiftErrorType::IllegalState { operation, state: ProcessState::default() })
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Returns the ID of the only game session hosted by the process.
+    pub fn current_game_session_id(
+        &self,
+    ) -> Result<crate::entity::GameSessionId, GameLiftErrorType> {
+        let session_state = self.session_state.read();
+        let mut game_session_ids = session_state.game_sessions.keys();
+        match (game_session_ids.next(), game_session_ids.next()) {
+            (Some(game_session_id), None) => Ok(game_session_id.clone()),
+            (None, _) => Err(GameLiftErrorType::GameSessionIdNotSet),
+            (Some(_), Some(_)) => Err(GameLiftErrorType::AmbiguousGameSession),
+        }
+    }
+
+    /// Returns the given game session ID, or the ID of the only game session
+    /// if none is given. Fails if the process is not ready.
+    fn resolve_game_session_id(
+        &self,
+        operation: &'static str,
+        game_session_id: Option<crate::entity::GameSessionId>,
+    ) -> Result<crate::entity::GameSessionId, GameLiftErrorType> {
+        self.check_state(operation, READY_STATES)?;
+        match game_session_id {
+            Some(game_session_id) => Ok(game_session_id),
+            None => self.current_game_session_id(),
+        }
+    }
+
+    pub fn game_session_ids(&self) -> Vec<crate::entity::GameSessionId> {
+        self.session_state.read().game_sessions.keys().cloned().collect()
+    }
+
+    pub fn game_session_state(&self, game_session_id: &str) -> Option<GameSessionState> {
+        self.session_state.read().game_sessions.get(game_session_id).map(|entry| entry.state)
+    }
+
+    pub fn game_session(&self, game_session_id: &str) -> Option<crate::entity::GameSession> {
+        self.session_state
+            .read()
+            .game_sessions
+            .get(game_session_id)
+            .map(|entry| entry.game_session.clone())
+    }
+
+    fn set_state(&self, to: ProcessState) -> ProcessState {
+        let from = std::mem::replace(&mut self.session_state.write().state, to);
+        self.on_state_changed(from, to);
+        from
+    }
+
+    fn on_state_changed(&self, from: ProcessState, to: ProcessState) {
+        if from != to {
+            log::debug!("Process state changed from {} to {}.", from, to);
+            self.notify(crate::notification::Notification::StateChanged { from, to });
+        }
     }
 
     pub fn get_termination_time(&self) -> Option<crate::entity::TerminationTimeType> {
         self.session_state.read().termination_time
     }
 
+    pub fn subscribe_notifications(
+        &self,
+    ) -> tokio::sync::broadcast::Receiver<crate::notification::Notification> {
+        self.notification_sender.subscribe()
+    }
+
+    pub fn start_draining(&self) {
+        if !std::mem::replace(&mut self.session_state.write().draining, true) {
+            log::debug!("The process stops accepting new players.");
+            self.notify(crate::notification::Notification::Draining);
+        }
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.session_state.read().draining
+    }
+
+    pub fn notify(&self, notification: crate::notification::Notification) {
+        // An error here only means that nobody is subscribed at the moment.
+        let _ = self.notification_sender.send(notification);
+    }
+
+    /// Hands the event over to the game server. Returns `false` if the process
+    /// is not registered or the game server has stopped listening.
+    async fn send_event(&self, event: crate::sdk_event::SdkEvent) -> bool {
+        let event_sender = match self.process_registration.read().as_ref() {
+            Some(process_registration) => process_registration.event_sender.clone(),
+            None => {
+                log::warn!("Got {:?} before ProcessReady. Ignoring.", event);
+                return false;
+            }
+        };
+        if event_sender.send(event).await.is_err() {
+            log::warn!("The game server no longer receives SDK events.");
+            return false;
+        }
+        true
+    }
+
+    /// Restores the AuxProxy view of this process after the WebSocket
+    /// connection has been re-established: AuxProxy may have been restarted
+    /// and lost everything it knew about us.
+    pub async fn on_reconnected(&self, attempts: u32) {
+        if self.is_process_ready() {
+            let process_registration = self.process_registration.read().clone();
+            if let Some(process_registration) = process_registration {
+                log::info!("Re-announcing ProcessReady after reconnection.");
+                if let Err(error) = self
+                    .transport
+                    .process_ready(process_registration.port, process_registration.log_paths)
+                    .await
+                {
+                    log::warn!("Could not re-announce ProcessReady: {:?}", error);
+                }
+            }
+
+            // Starting game sessions are left out: announcing them would
+            // activate them before the game server is ready. The game server
+            // still activates them itself, and AuxProxy gets that call like
+            // any other.
+            let activated_game_session_ids: Vec<_> = self
+                .session_state
+                .read()
+                .game_sessions
+                .iter()
+                .filter(|(_, entry)| entry.state == GameSessionState::Active)
+                .map(|(game_session_id, _)| game_session_id.clone())
+                .collect();
+            for game_session_id in activated_game_session_ids {
+                log::info!("Re-announcing the active game session {}.", game_session_id);
+                if let Err(error) = self.transport.activate_game_session(game_session_id).await {
+                    log::warn!("Could not re-announce the active game session: {:?}", error);
+                }
+            }
+        }
+
+        self.notify(crate::notification::Notification::Reconnected { attempts });
+    }
+
     pub async fn on_start_game_session(&self, game_session: crate::entity::GameSession) {
-        if !self.is_process_ready() {
-            log::debug!("Got a game session on inactive process. Ignoring.");
+        let game_session_id = match game_session.game_session_id.clone() {
+            Some(game_session_id) => game_session_id,
+            None => {
+                log::warn!("Got a game session without an ID. Ignoring.");
+                return;
+            }
+        };
+
+        if let Err(error) = self.start_game_session(game_session_id, &game_session) {
+            log::warn!("Got a game session that cannot be started: {:?}. Ignoring.", error);
             return;
         }
+        self.send_event(crate::sdk_event::SdkEvent::StartGameSession(game_session)).await;
+    }
 
-        self.session_state.write().game_session_id =
-            Some(game_session.game_session_id.clone().unwrap());
-        (self.process_parameters.as_ref().unwrap().on_start_game_session)(game_session).await;
+    fn start_game_session(
+        &self,
+        game_session_id: crate::entity::GameSessionId,
+        game_session: &crate::entity::GameSession,
+    ) -> Result<(), GameLiftErrorType> {
+        let max_concurrent_game_sessions = self.max_concurrent_game_sessions;
+        self.update_game_sessions("start_game_session", |game_sessions| {
+            if game_sessions.contains_key(&game_session_id) {
+                return Err(GameLiftErrorType::DuplicateGameSession(game_session_id));
+            }
+            if game_sessions.len() >= max_concurrent_game_sessions {
+                return Err(GameLiftErrorType::GameSessionCapacityReached(
+                    max_concurrent_game_sessions,
+                ));
+            }
+            game_sessions.insert(
+                game_session_id,
+                GameSessionEntry {
+                    state: GameSessionState::Starting,
+                    game_session: game_session.clone(),
+                    player_sessions: Default::default(),
+                    backfill_tickets: Default::default(),
+                },
+            );
+            Ok(())
+        })
     }
 
-    pub async fn on_terminate_process(&self, termination_time: i64) {
+    pub async fn on_terminate_process(&self, termination_time: Option<i64>) {
         log::debug!(
-            "ServerState got the terminateProcess signal. TerminateProcess: {}",
+            "ServerState got the terminateProcess signal. TerminateProcess: {:?}",
             termination_time
         );
-        self.session_state.write().termination_time = Some(termination_time);
-        (self.process_parameters.as_ref().unwrap().on_process_terminate)().await;
+        self.session_state.write().termination_time = termination_time;
+        self.notify(crate::notification::Notification::TerminationRequested { termination_time });
+        self.send_event(crate::sdk_event::SdkEvent::ProcessTerminate { termination_time }).await;
     }
 
     pub async fn on_update_game_session(
@@ -60,13 +708,29 @@ impl ServerStateInner {
             log::warn!("Got an updated game session on inactive process.");
             return;
         }
-        (self.process_parameters.as_ref().unwrap().on_update_game_session)(
+
+        if let Some(game_session_id) = game_session.game_session_id.clone() {
+            // Keep the latest matchmaker data for the next backfill request.
+            if let Some(entry) = self.session_state.write().game_sessions.get_mut(&game_session_id)
+            {
+                entry.game_session = game_session.clone();
+            }
+            if let Some(state) =
+                crate::backfill::BackfillTicketState::from_update_reason(update_reason)
+            {
+                if !backfill_ticket_id.is_empty() {
+                    self.finish_backfill_ticket(&game_session_id, &backfill_ticket_id, state);
+                }
+            }
+        }
+
+        self.send_event(crate::sdk_event::SdkEvent::UpdateGameSession(
             crate::entity::UpdateGameSession {
                 game_session: Some(game_session),
                 update_reason,
                 backfill_ticket_id,
             },
-        )
+        ))
         .await;
     }
 
@@ -76,19 +740,23 @@ impl ServerStateInner {
             return;
         }
 
-        log::debug!("Reporting health using the OnHealthCheck callback.");
-
-        let result = tokio::time::timeout(
-            std::time::Duration::from_secs(HEALTHCHECK_TIMEOUT_SECONDS),
-            (self.process_parameters.as_ref().unwrap().on_health_check)(),
-        )
-        .await;
+        log::debug!("Requesting the health status from the game server.");
 
-        let report_health_result = if let Ok(health_check_result) = result {
-            self.http_client.report_health(health_check_result).await
-        } else {
-            self.http_client.report_health(false).await
+        let (responder, health_check_result) = crate::sdk_event::HealthCheckResponder::new();
+        let callback = async {
+            self.send_event(crate::sdk_event::SdkEvent::HealthCheck(responder)).await
+                && health_check_result.await.unwrap_or(false)
         };
+        let snapshot = self.health_registry.run_checks(self.health_check.timeout, callback).await;
+        for status in snapshot.unhealthy_checks() {
+            log::warn!(
+                "The health check {} is unhealthy: {}",
+                status.name,
+                status.last_result.reason.as_deref().unwrap_or("no reason given")
+            );
+        }
+
+        let report_health_result = self.transport.report_health(snapshot.healthy).await;
 
         if let Err(error) = report_health_result {
             log::warn!("Could not send health starus: {:?}", error);
@@ -97,191 +765,598 @@ impl ServerStateInner {
 }
 
 pub struct ServerState {
-    inner: std::sync::Arc<tokio::sync::RwLock<ServerStateInner>>,
-    websocket_listener: Option<crate::web_socket_listener::WebSocketListener>,
-    health_report_task: Option<JoinHandle<()>>,
+    config: crate::sdk_config::SdkConfig,
+    inner: std::sync::Arc<ServerStateInner>,
+    websocket_listener: parking_lot::Mutex<Option<crate::web_socket_listener::WebSocketListener>>,
+    health_report_task: parking_lot::Mutex<Option<JoinHandle<()>>>,
 }
 
-impl Default for ServerState {
-    fn default() -> Self {
+impl ServerState {
+    pub fn new(config: crate::sdk_config::SdkConfig) -> Self {
         Self {
-            inner: std::sync::Arc::new(tokio::sync::RwLock::new(ServerStateInner::default())),
-            websocket_listener: None,
-            health_report_task: None,
+            inner: std::sync::Arc::new(ServerStateInner::new(&config)),
+            config,
+            websocket_listener: Default::default(),
+            health_report_task: Default::default(),
         }
     }
-}
 
-impl ServerState {
+    pub fn sdk_version(&self) -> &'static str {
+        self.inner.sdk_version()
+    }
+
+    pub fn state(&self) -> ProcessState {
+        self.inner.state()
+    }
+
     pub async fn process_ready(
-        &mut self,
+        &self,
         process_parameters: crate::process_parameters::ProcessParameters,
     ) -> Result<(), crate::error::GameLiftErrorType> {
+        self.inner.transition(
+            "process_ready",
+            &[ProcessState::Initialized],
+            ProcessState::Ready,
+        )?;
         let port = process_parameters.port;
         let log_paths = process_parameters.log_parameters.log_paths.clone();
 
-        let result = {
-            let mut inner = self.inner.write().await;
+        let events = self.register_process(port, log_paths).await?;
+        tokio::spawn(crate::dispatcher::run_callbacks(
+            process_parameters,
+            events,
+            self.config.callback_ordering,
+            self.config.callback_timeout,
+        ));
+        Ok(())
+    }
+
+    pub async fn process_ready_with_events(
+        &self,
+        port: i32,
+        log_parameters: crate::log_parameters::LogParameters,
+    ) -> Result<crate::sdk_event::SdkEvents, crate::error::GameLiftErrorType> {
+        self.inner.transition(
+            "process_ready",
+            &[ProcessState::Initialized],
+            ProcessState::Ready,
+        )?;
+        self.register_process(port, log_parameters.log_paths).await
+    }
 
-            inner.session_state.write().is_process_ready = true;
-            inner.process_parameters = Some(process_parameters);
+    /// Sends ProcessReady after the transition to Ready. If it fails, the
+    /// process goes back to Initialized, so that ProcessReady can be retried.
+    async fn register_process(
+        &self,
+        port: i32,
+        log_paths: Vec<String>,
+    ) -> Result<crate::sdk_event::SdkEvents, crate::error::GameLiftErrorType> {
+        let inner = &self.inner;
+        let (event_sender, event_receiver) = tokio::sync::mpsc::channel(EVENT_CHANNEL_CAPACITY);
 
-            inner.http_client.process_ready(port, log_paths).await
-        };
+        // Registered before sending ProcessReady, since AuxProxy may start a
+        // game session right after it.
+        *inner.process_registration.write() =
+            Some(ProcessRegistration { port, log_paths: log_paths.clone(), event_sender });
 
-        self.start_health_check().await;
+        if let Err(error) = inner.transport.process_ready(port, log_paths).await {
+            // The process may have been destroyed in the meantime.
+            if inner
+                .transition("process_ready", &[ProcessState::Ready], ProcessState::Initialized)
+                .is_ok()
+            {
+                *inner.process_registration.write() = None;
+            }
+            return Err(error);
+        }
 
-        result
+        self.start_health_check();
+        Ok(crate::sdk_event::SdkEvents::new(event_receiver))
     }
 
     pub async fn process_ending(&self) -> Result<(), crate::error::GameLiftErrorType> {
-        let inner = self.inner.read().await;
+        let inner = &self.inner;
 
-        inner.session_state.write().is_process_ready = false;
-        inner.http_client.process_ending().await
+        inner.transition(
+            "process_ending",
+            &[
+                ProcessState::Initialized,
+                ProcessState::Ready,
+                ProcessState::SessionStarting,
+                ProcessState::SessionActive,
+                ProcessState::Ending,
+            ],
+            ProcessState::Ending,
+        )?;
+        inner.transport.process_ending().await?;
+        // The process may have been destroyed in the meantime, then it has
+        // ended already.
+        if inner.transition("process_ending", &[ProcessState::Ending], ProcessState::Ended).is_ok()
+        {
+            inner.on_process_ended();
+        }
+        Ok(())
     }
 
-    pub async fn activate_game_session(&self) -> Result<(), GameLiftErrorType> {
-        let inner = self.inner.read().await;
+    /// Activates the given game session, or the only one hosted by the
+    /// process if `game_session_id` is `None`. The same applies to the other
+    /// game session operations.
+    pub async fn activate_game_session(
+        &self,
+        game_session_id: Option<crate::entity::GameSessionId>,
+    ) -> Result<(), GameLiftErrorType> {
+        let inner = &self.inner;
+        let operation = "activate_game_session";
+        let allowed = &[GameSessionState::Starting];
 
-        let game_session_id = inner.get_game_session_id();
-        if let Some(game_session_id) = game_session_id {
-            inner.http_client.activate_game_session(game_session_id).await
-        } else {
-            Err(crate::error::GameLiftErrorType::GameSessionIdNotSet)
-        }
+        let game_session_id = inner.resolve_game_session_id(operation, game_session_id)?;
+        inner.check_game_session(operation, &game_session_id, allowed)?;
+        inner.transport.activate_game_session(game_session_id.clone()).await?;
+        inner.update_game_session(
+            operation,
+            &game_session_id,
+            allowed,
+            Some(GameSessionState::Active),
+        )
     }
 
-    pub async fn terminate_game_session(&self) -> Result<(), GameLiftErrorType> {
-        let inner = self.inner.read().await;
+    pub async fn terminate_game_session(
+        &self,
+        game_session_id: Option<crate::entity::GameSessionId>,
+    ) -> Result<(), GameLiftErrorType> {
+        let inner = &self.inner;
+        let operation = "terminate_game_session";
+        let allowed = &[GameSessionState::Starting, GameSessionState::Active];
 
-        let game_session_id = inner.get_game_session_id();
-        if let Some(game_session_id) = game_session_id {
-            inner.http_client.terminate_game_session(game_session_id).await
-        } else {
-            Err(crate::error::GameLiftErrorType::GameSessionIdNotSet)
-        }
+        let game_session_id = inner.resolve_game_session_id(operation, game_session_id)?;
+        inner.check_game_session(operation, &game_session_id, allowed)?;
+        inner.transport.terminate_game_session(game_session_id.clone()).await?;
+        inner.update_game_session(operation, &game_session_id, allowed, None)
     }
 
     pub async fn get_game_session_id(
         &self,
     ) -> Result<crate::entity::GameSessionId, crate::error::GameLiftErrorType> {
-        match self.inner.read().await.get_game_session_id() {
-            Some(game_session_id) => Ok(game_session_id),
-            None => Err(crate::error::GameLiftErrorType::GameSessionIdNotSet),
-        }
+        self.inner.current_game_session_id()
+    }
+
+    pub fn game_session_ids(&self) -> Vec<crate::entity::GameSessionId> {
+        self.inner.game_session_ids()
+    }
+
+    pub fn game_session_state(&self, game_session_id: &str) -> Option<GameSessionState> {
+        self.inner.game_session_state(game_session_id)
+    }
+
+    pub fn game_session(&self, game_session_id: &str) -> Option<crate::entity::GameSession> {
+        self.inner.game_session(game_session_id)
+    }
+
+    pub fn player_sessions(
+        &self,
+        game_session_id: &str,
+    ) -> Vec<crate::player_session::PlayerSessionRecord> {
+        self.inner.player_sessions(game_session_id)
+    }
+
+    pub fn player_session(
+        &self,
+        game_session_id: &str,
+        player_session_id: &str,
+    ) -> Option<crate::player_session::PlayerSessionRecord> {
+        self.inner.player_session(game_session_id, player_session_id)
+    }
+
+    pub fn player_count(&self, game_session_id: Option<&str>) -> usize {
+        self.inner.player_count(game_session_id)
+    }
+
+    pub fn max_concurrent_game_sessions(&self) -> usize {
+        self.inner.max_concurrent_game_sessions
+    }
+
+    /// Whether the game session has ended or is about to.
+    pub fn is_game_session_ending(&self, game_session_id: &str) -> bool {
+        self.game_session_state(game_session_id).is_none()
+            || !self.state().is_process_ready()
+            || self.inner.get_termination_time().is_some()
     }
 
     pub async fn get_termination_time(
         &self,
     ) -> Result<crate::entity::TerminationTimeType, crate::error::GameLiftErrorType> {
-        match self.inner.read().await.get_termination_time() {
+        match self.inner.get_termination_time() {
             Some(value) => Ok(value),
             None => Err(crate::error::GameLiftErrorType::TerminationTimeNotSet),
         }
     }
 
+    pub fn start_draining(&self) {
+        self.inner.start_draining()
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.inner.is_draining()
+    }
+
     pub async fn update_player_session_creation_policy(
         &self,
+        game_session_id: Option<crate::entity::GameSessionId>,
         player_session_policy: crate::entity::PlayerSessionCreationPolicy,
     ) -> Result<(), GameLiftErrorType> {
-        let inner = self.inner.read().await;
-
-        let game_session_id = inner.get_game_session_id();
-        if let Some(game_session_id) = game_session_id {
-            inner
-                .http_client
-                .update_player_session_creation_policy(game_session_id, player_session_policy)
-                .await
-        } else {
-            Err(crate::error::GameLiftErrorType::GameSessionIdNotSet)
-        }
+        let inner = &self.inner;
+        let operation = "update_player_session_creation_policy";
+
+        let game_session_id = inner.resolve_game_session_id(operation, game_session_id)?;
+        inner.check_game_session(
+            operation,
+            &game_session_id,
+            &[GameSessionState::Starting, GameSessionState::Active],
+        )?;
+        inner
+            .transport
+            .update_player_session_creation_policy(game_session_id, player_session_policy)
+            .await
     }
 
     pub async fn accept_player_session(
         &self,
+        game_session_id: Option<crate::entity::GameSessionId>,
         player_session_id: crate::entity::PlayerSessionId,
     ) -> Result<(), GameLiftErrorType> {
-        let inner = self.inner.read().await;
+        let inner = &self.inner;
+        let operation = "accept_player_session";
 
-        let game_session_id = inner.get_game_session_id();
-        if let Some(game_session_id) = game_session_id {
-            inner.http_client.accept_player_session(player_session_id, game_session_id).await
+        let game_session_id = inner.resolve_game_session_id(operation, game_session_id)?;
+        let previous = inner.update_player_sessions(
+            operation,
+            &game_session_id,
+            &[GameSessionState::Active],
+            |player_sessions| player_sessions.accept(&player_session_id),
+        )?;
+        let result = inner
+            .transport
+            .accept_player_session(player_session_id.clone(), game_session_id.clone())
+            .await;
+        if result.is_err() {
+            // The game session may have ended in the meantime.
+            let _ = inner.update_player_sessions(
+                operation,
+                &game_session_id,
+                &[GameSessionState::Active],
+                |player_sessions| {
+                    player_sessions.restore(&player_session_id, previous);
+                    Ok(())
+                },
+            );
         } else {
-            Err(crate::error::GameLiftErrorType::GameSessionIdNotSet)
+            inner.on_player_count_changed(&game_session_id);
         }
+        result
     }
 
     pub async fn remove_player_session(
         &self,
+        game_session_id: Option<crate::entity::GameSessionId>,
         player_session_id: crate::entity::PlayerSessionId,
     ) -> Result<(), GameLiftErrorType> {
-        let inner = self.inner.read().await;
+        let inner = &self.inner;
+        let operation = "remove_player_session";
 
-        let game_session_id = inner.get_game_session_id();
-        if let Some(game_session_id) = game_session_id {
-            inner.http_client.remove_player_session(player_session_id, game_session_id).await
-        } else {
-            Err(crate::error::GameLiftErrorType::GameSessionIdNotSet)
-        }
+        let game_session_id = inner.resolve_game_session_id(operation, game_session_id)?;
+        inner.check_game_session(operation, &game_session_id, &[GameSessionState::Active])?;
+        inner
+            .transport
+            .remove_player_session(player_session_id.clone(), game_session_id.clone())
+            .await?;
+        // The game session may have ended in the meantime.
+        let _ = inner.update_player_sessions(
+            operation,
+            &game_session_id,
+            &[GameSessionState::Active],
+            |player_sessions| {
+                player_sessions.remove(&player_session_id);
+                Ok(())
+            },
+        );
+        inner.on_player_count_changed(&game_session_id);
+        Ok(())
     }
 
     pub async fn describe_player_sessions(
         &self,
         request: crate::entity::DescribePlayerSessionsRequest,
     ) -> Result<crate::entity::DescribePlayerSessionsResult, GameLiftErrorType> {
-        self.inner.read().await.http_client.describe_player_sessions(request).await
+        let result = self.inner.transport.describe_player_sessions(request).await?;
+        self.inner.record_player_sessions(&result.player_sessions);
+        Ok(result)
     }
 
     pub async fn backfill_matchmaking(
         &self,
         request: crate::entity::StartMatchBackfillRequest,
     ) -> Result<crate::entity::StartMatchBackfillResult, GameLiftErrorType> {
-        self.inner.read().await.http_client.backfill_matchmaking(request).await
+        self.inner.transport.backfill_matchmaking(request).await
     }
 
     pub async fn stop_matchmaking(
         &self,
         request: crate::entity::StopMatchBackfillRequest,
     ) -> Result<(), GameLiftErrorType> {
-        self.inner.read().await.http_client.stop_matchmaking(request).await
+        self.inner.transport.stop_matchmaking(request).await
+    }
+
+    pub async fn start_backfill(
+        &self,
+        game_session_id: &str,
+    ) -> Result<crate::backfill::BackfillTicket, GameLiftErrorType> {
+        let inner = &self.inner;
+        let operation = "start_backfill";
+
+        inner.check_state(operation, READY_STATES)?;
+        self.describe_removed_player_sessions(game_session_id).await?;
+        let (ticket, request) = inner.start_backfill_ticket(operation, game_session_id)?;
+        if let Err(error) = inner.transport.backfill_matchmaking(request).await {
+            // The game session may have ended in the meantime.
+            let _ = inner.update_backfill_tickets(operation, game_session_id, |tickets| {
+                tickets.discard(&ticket.ticket_id);
+                Ok(())
+            });
+            return Err(error);
+        }
+        Ok(ticket)
+    }
+
+    /// Describes the player sessions of the game session if some removed ones
+    /// have no player ID yet. Accepting and removing a player session only
+    /// knows its ID, but a backfill request must leave out the players who
+    /// left.
+    async fn describe_removed_player_sessions(
+        &self,
+        game_session_id: &str,
+    ) -> Result<(), GameLiftErrorType> {
+        let unresolved = self.inner.player_sessions(game_session_id).iter().any(|record| {
+            record.state == crate::player_session::PlayerSessionState::Removed
+                && record.player_id.is_none()
+        });
+        if !unresolved {
+            return Ok(());
+        }
+
+        let mut request = crate::entity::DescribePlayerSessionsRequest {
+            game_session_id: Some(game_session_id.to_string()),
+            limit: crate::player_session::DEFAULT_PAGE_SIZE,
+            ..Default::default()
+        };
+        loop {
+            let result = self.describe_player_sessions(request.clone()).await?;
+            if result.next_token.is_empty() {
+                return Ok(());
+            }
+            request.next_token = Some(result.next_token);
+        }
+    }
+
+    pub async fn stop_backfill(&self, game_session_id: &str) -> Result<(), GameLiftErrorType> {
+        let inner = &self.inner;
+        let operation = "stop_backfill";
+
+        inner.check_state(operation, READY_STATES)?;
+        let (ticket, matchmaking_configuration_arn) = inner.update_game_session_entry(
+            operation,
+            game_session_id,
+            &[GameSessionState::Starting, GameSessionState::Active],
+            |entry| {
+                let ticket =
+                    entry.backfill_tickets.active().ok_or(GameLiftErrorType::BackfillNotActive)?;
+                let matchmaker_data = entry
+                    .game_session
+                    .matchmaker_data()?
+                    .ok_or(GameLiftErrorType::MatchmakerDataNotSet)?;
+                Ok((ticket, matchmaker_data.matchmaking_configuration_arn))
+            },
+        )?;
+        inner
+            .transport
+            .stop_matchmaking(crate::entity::StopMatchBackfillRequest {
+                ticket_id: Some(ticket.ticket_id.clone()),
+                game_session_arn: Some(game_session_id.to_string()),
+                matchmaking_configuration_arn: Some(matchmaking_configuration_arn),
+            })
+            .await?;
+        inner.finish_backfill_ticket(
+            game_session_id,
+            &ticket.ticket_id,
+            crate::backfill::BackfillTicketState::Cancelled,
+        );
+        Ok(())
+    }
+
+    pub fn backfill_tickets(&self, game_session_id: &str) -> Vec<crate::backfill::BackfillTicket> {
+        self.inner.backfill_tickets(game_session_id)
+    }
+
+    pub fn backfill_ticket(
+        &self,
+        game_session_id: &str,
+        ticket_id: &str,
+    ) -> Option<crate::backfill::BackfillTicket> {
+        self.inner.backfill_ticket(game_session_id, ticket_id)
+    }
+
+    pub fn active_backfill_ticket(
+        &self,
+        game_session_id: &str,
+    ) -> Option<crate::backfill::BackfillTicket> {
+        self.inner.active_backfill_ticket(game_session_id)
+    }
+
+    pub fn health_registry(&self) -> crate::health::HealthRegistry {
+        self.inner.health_registry.clone()
+    }
+
+    pub fn subscribe_notifications(
+        &self,
+    ) -> tokio::sync::broadcast::Receiver<crate::notification::Notification> {
+        self.inner.subscribe_notifications()
     }
 
-    async fn start_health_check(&mut self) {
+    fn start_health_check(&self) {
         log::debug!("Health check started.");
 
         let inner_state = self.inner.clone();
         let report_health_task = async move {
-            while inner_state.read().await.is_process_ready() {
-                {
-                    inner_state.read().await.report_health().await;
-                }
+            while inner_state.is_process_ready() {
+                inner_state.report_health().await;
 
-                tokio::time::sleep(std::time::Duration::from_secs(HEALTHCHECK_TIMEOUT_SECONDS))
-                    .await;
+                let health_check = inner_state.health_check;
+                tokio::time::sleep(
+                    health_check.interval + crate::health::random_jitter(health_check.jitter),
+                )
+                .await;
             }
         };
 
-        self.health_report_task = Some(tokio::spawn(report_health_task));
+        // Only one health check loop per process, even if ProcessReady is sent
+        // again.
+        if let Some(previous_task) =
+            self.health_report_task.lock().replace(tokio::spawn(report_health_task))
+        {
+            previous_task.abort();
+        }
     }
 
-    pub async fn initialize_networking(&mut self) -> Result<(), crate::error::GameLiftErrorType> {
-        self.websocket_listener =
-            Some(crate::web_socket_listener::WebSocketListener::new(self.inner.clone()));
-        self.websocket_listener.as_mut().unwrap().connect().await
+    pub async fn initialize_networking(&self) -> Result<(), crate::error::GameLiftErrorType> {
+        self.inner.check_state("init_sdk", &[ProcessState::NotInitialized])?;
+        self.connect().await?;
+        self.inner.transition(
+            "init_sdk",
+            &[ProcessState::NotInitialized],
+            ProcessState::Initialized,
+        )
+    }
+
+    async fn connect(&self) -> Result<(), crate::error::GameLiftErrorType> {
+        if let crate::transport::Transport::WebSocket(client) = &self.inner.transport {
+            return client.connect(self.inner.clone()).await;
+        }
+
+        let mut websocket_listener = crate::web_socket_listener::WebSocketListener::new(
+            self.inner.clone(),
+            self.config.clone(),
+        );
+        websocket_listener.connect().await?;
+        if let Some(previous_listener) = self.websocket_listener.lock().replace(websocket_listener)
+        {
+            previous_listener.disconnect();
+        }
+        Ok(())
     }
 
     pub async fn get_instance_certificate(
         &self,
     ) -> Result<GetInstanceCertificateResult, GameLiftErrorType> {
-        self.inner.read().await.http_client.get_instance_certificate().await
+        self.inner.transport.get_instance_certificate().await
     }
 
     pub async fn shutdown(&self) -> bool {
-        self.inner.read().await.session_state.write().is_process_ready = false;
-        if let Some(health_report_task) = &self.health_report_task {
+        if self.inner.set_state(ProcessState::Ended) != ProcessState::Ended {
+            self.inner.on_process_ended();
+        }
+        // Ends the event stream.
+        *self.inner.process_registration.write() = None;
+        if let Some(health_report_task) = self.health_report_task.lock().as_ref() {
             health_report_task.abort();
         }
-        self.websocket_listener.as_ref().unwrap().disconnect()
+        if let crate::transport::Transport::WebSocket(client) = &self.inner.transport {
+            return client.disconnect();
+        }
+        self.websocket_listener.lock().as_ref().is_some_and(|listener| listener.disconnect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::GameLiftErrorType,
+        notification::Notification,
+        server_state::{ProcessState, ServerState},
+    };
+
+    #[test]
+    fn sdk_version_test() {
+        let config = crate::sdk_config::SdkConfig::default();
+        assert_eq!(ServerState::new(config.clone()).sdk_version(), crate::api::SDK_VERSION);
+        let config = crate::sdk_config::SdkConfig {
+            protocol: crate::sdk_config::Protocol::WebSocket(Default::default()),
+            ..config
+        };
+        assert_eq!(ServerState::new(config).sdk_version(), "5.1.0");
+    }
+
+    #[test]
+    fn start_game_session_test() {
+        let state = ServerState::new(crate::sdk_config::SdkConfig::default());
+        state.inner.session_state.write().state = ProcessState::Ready;
+        let game_session = crate::entity::GameSession::default();
+
+        state.inner.start_game_session("gsess-1".to_string(), &game_session).unwrap();
+        assert_eq!(state.state(), ProcessState::SessionStarting);
+        assert!(matches!(
+            state.inner.start_game_session("gsess-1".to_string(), &game_session),
+            Err(GameLiftErrorType::DuplicateGameSession(game_session_id)) if game_session_id == "gsess-1"
+        ));
+        assert!(matches!(
+            state.inner.start_game_session("gsess-2".to_string(), &game_session),
+            Err(GameLiftErrorType::GameSessionCapacityReached(1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn illegal_state_test() {
+        let state = ServerState::new(crate::sdk_config::SdkConfig::default());
+        let mut notifications = state.inner.subscribe_notifications();
+
+        assert!(matches!(
+            state.activate_game_session(None).await,
+            Err(GameLiftErrorType::IllegalState {
+                operation: "activate_game_session",
+                state: ProcessState::NotInitialized
+            })
+        ));
+        assert!(matches!(
+            state
+                .process_ready_with_events(
+                    14000,
+                    crate::log_parameters::LogParameters { log_paths: vec![] }
+                )
+                .await,
+            Err(GameLiftErrorType::IllegalState { operation: "process_ready", .. })
+        ));
+
+        // A game session is only started on a ready process.
+        state
+            .inner
+            .on_start_game_session(crate::entity::GameSession {
+                game_session_id: Some("gsess-1".to_string()),
+                ..Default::default()
+            })
+            .await;
+        assert_eq!(state.state(), ProcessState::NotInitialized);
+        assert!(state.get_game_session_id().await.is_err());
+
+        assert!(!state.shutdown().await);
+        assert_eq!(state.state(), ProcessState::Ended);
+        assert_eq!(
+            notifications.try_recv().unwrap(),
+            Notification::StateChanged {
+                from: ProcessState::NotInitialized,
+                to: ProcessState::Ended
+            }
+        );
+        assert!(matches!(
+            state.accept_player_session(None, "psess-1".to_string()).await,
+            Err(GameLiftErrorType::IllegalState { state: ProcessState::Ended, .. })
+        ));
     }
 }
@@ -0,0 +1,202 @@
+pub type OnShutdownOutputType =
+    std::pin::Pin<Box<dyn std::future::Future<Output = ()> + std::marker::Send>>;
+pub type OnShutdownType =
+    dyn Fn(ShutdownContext) -> OnShutdownOutputType + std::marker::Send + std::marker::Sync;
+
+/// What has triggered the shutdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+pub enum ShutdownReason {
+    /// GameLift has sent TerminateProcess, e.g. on scale-down or a spot
+    /// interruption.
+    TerminateProcess,
+
+    /// The process has received SIGTERM, or Ctrl+C on platforms without it.
+    Signal,
+
+    /// The game server has called [ShutdownCoordinator::shutdown].
+    Requested,
+}
+
+/// Passed to the shutdown hooks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShutdownContext {
+    pub reason: ShutdownReason,
+
+    /// When the process will be killed. Either the termination time sent by
+    /// GameLift or the end of
+    /// [drain_timeout](ShutdownConfig::drain_timeout).
+    pub deadline: std::time::SystemTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShutdownConfig {
+    /// How long before the deadline the coordinator stops waiting for players
+    /// and ends the process.
+    pub safety_margin: std::time::Duration,
+
+    /// How long to wait for players if there is no termination time, e.g.
+    /// after SIGTERM.
+    pub drain_timeout: std::time::Duration,
+
+    /// Whether [ShutdownCoordinator::run] also shuts down on SIGTERM.
+    pub handle_signals: bool,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            safety_margin: std::time::Duration::from_secs(10),
+            drain_timeout: std::time::Duration::from_secs(30),
+            handle_signals: true,
+        }
+    }
+}
+
+/// Shuts the server process down gracefully: it stops new players from
+/// joining, runs the shutdown hooks, waits for the connected players to leave
+/// until shortly before the deadline, sends ProcessEnding and destroys the
+/// SDK.
+pub struct ShutdownCoordinator {
+    api: crate::api::Api,
+    config: ShutdownConfig,
+    hooks: Vec<Box<OnShutdownType>>,
+}
+
+impl ShutdownCoordinator {
+    pub fn new(api: crate::api::Api, config: ShutdownConfig) -> Self {
+        Self { api, config, hooks: Vec::new() }
+    }
+
+    /// Adds a hook that is invoked once the process stops accepting players.
+    /// Hooks run one after another and are cancelled at the deadline minus the
+    /// safety margin.
+    pub fn on_shutdown(mut self, hook: Box<OnShutdownType>) -> Self {
+        self.hooks.push(hook);
+        self
+    }
+
+    /// Waits for TerminateProcess (or SIGTERM, see
+    /// [handle_signals](ShutdownConfig::handle_signals)), shuts down and
+    /// returns the exit code to end the process with.
+    pub async fn run(&self) -> i32 {
+        let reason = self.wait_for_trigger().await;
+        self.shutdown(reason).await
+    }
+
+    /// Shuts down right away and returns the exit code to end the process
+    /// with: 0 if GameLift has been notified, 1 otherwise.
+    pub async fn shutdown(&self, reason: ShutdownReason) -> i32 {
+        let deadline = self.deadline(reason).await;
+        let drain_until = deadline.checked_sub(self.config.safety_margin).unwrap_or(deadline);
+        let drain_until = tokio::time::Instant::now()
+            + drain_until.duration_since(std::time::SystemTime::now()).unwrap_or_default();
+        log::info!("Shutting down after {}, deadline {:?}.", reason, deadline);
+
+        let mut notifications = self.api.subscribe_notifications();
+        self.api.start_draining();
+        for game_session in self.api.game_sessions() {
+            if let Err(error) = game_session
+                .update_player_session_creation_policy(
+                    crate::entity::PlayerSessionCreationPolicy::DenyAll,
+                )
+                .await
+            {
+                log::warn!(
+                    "Could not deny new players in the game session {}: {:?}",
+                    game_session.game_session_id(),
+                    error
+                );
+            }
+        }
+
+        let context = ShutdownContext { reason, deadline };
+        for hook in &self.hooks {
+            if tokio::time::timeout_at(drain_until, hook(context.clone())).await.is_err() {
+                log::warn!("A shutdown hook was cancelled at the deadline.");
+            }
+        }
+
+        let drained = tokio::time::timeout_at(drain_until, async {
+            while self.api.player_count() > 0 {
+                if let Err(tokio::sync::broadcast::error::RecvError::Closed) =
+                    notifications.recv().await
+                {
+                    return;
+                }
+            }
+        })
+        .await;
+        if drained.is_err() {
+            log::warn!(
+                "Ending the process with {} players still connected.",
+                self.api.player_count()
+            );
+        }
+
+        let exit_code = match self.api.process_ending().await {
+            Ok(()) => 0,
+            Err(error) => {
+                log::error!("Could not notify GameLift about the process ending: {:?}", error);
+                1
+            }
+        };
+        self.api.destroy().await;
+        exit_code
+    }
+
+    async fn wait_for_trigger(&self) -> ShutdownReason {
+        let mut notifications = self.api.subscribe_notifications();
+        if self.api.get_termination_time().await.is_ok() {
+            return ShutdownReason::TerminateProcess;
+        }
+
+        let termination_requested = async {
+            loop {
+                match notifications.recv().await {
+                    Ok(crate::notification::Notification::TerminationRequested { .. }) => return,
+                    Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                        std::future::pending::<()>().await
+                    }
+                }
+            }
+        };
+        let signal = async {
+            if !self.config.handle_signals {
+                return std::future::pending().await;
+            }
+            if let Err(error) = terminate_signal().await {
+                log::warn!("Cannot listen for SIGTERM: {}", error);
+                std::future::pending::<()>().await;
+            }
+        };
+
+        tokio::select! {
+            _ = termination_requested => ShutdownReason::TerminateProcess,
+            _ = signal => ShutdownReason::Signal,
+        }
+    }
+
+    /// The termination time sent by GameLift, or the end of the drain timeout.
+    async fn deadline(&self, reason: ShutdownReason) -> std::time::SystemTime {
+        let countdown = match reason {
+            ShutdownReason::TerminateProcess => self.api.termination_countdown().await.ok(),
+            _ => None,
+        };
+        match countdown {
+            Some(countdown) if countdown.deadline() > std::time::UNIX_EPOCH => countdown.deadline(),
+            _ => std::time::SystemTime::now() + self.config.drain_timeout,
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn terminate_signal() -> std::io::Result<()> {
+    tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?.recv().await;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn terminate_signal() -> std::io::Result<()> {
+    tokio::signal::ctrl_c().await
+}
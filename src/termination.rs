@@ -0,0 +1,174 @@
+pub type OnCountdownOutputType =
+    std::pin::Pin<Box<dyn std::future::Future<Output = ()> + std::marker::Send>>;
+pub type OnCountdownType =
+    dyn Fn(CountdownTick) -> OnCountdownOutputType + std::marker::Send + std::marker::Sync;
+
+/// The time GameLift will shut the process down, see
+/// [get_termination_time](crate::api::Api::get_termination_time).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TerminationCountdown {
+    deadline: std::time::SystemTime,
+}
+
+impl TerminationCountdown {
+    pub fn new(deadline: std::time::SystemTime) -> Self {
+        Self { deadline }
+    }
+
+    /// Converts a termination time in seconds since the Unix epoch, as sent by
+    /// GameLift.
+    pub fn from_termination_time(termination_time: crate::entity::TerminationTimeType) -> Self {
+        let seconds = termination_time.max(0) as u64;
+        Self::new(std::time::UNIX_EPOCH + std::time::Duration::from_secs(seconds))
+    }
+
+    pub fn deadline(&self) -> std::time::SystemTime {
+        self.deadline
+    }
+
+    /// The deadline on the monotonic clock, e.g. for
+    /// `tokio::time::sleep_until`.
+    pub fn deadline_instant(&self) -> tokio::time::Instant {
+        tokio::time::Instant::now() + self.time_remaining()
+    }
+
+    /// Time left until the deadline, zero once it has passed.
+    pub fn time_remaining(&self) -> std::time::Duration {
+        self.deadline.duration_since(std::time::SystemTime::now()).unwrap_or_default()
+    }
+
+    /// Invokes `callback` when the time remaining reaches each of the
+    /// `offsets`, e.g. 120, 30 and 5 seconds before the deadline. Offsets that
+    /// have already passed are skipped. The callbacks stop when the returned
+    /// handle is dropped.
+    pub fn schedule(
+        &self,
+        offsets: Vec<std::time::Duration>,
+        callback: Box<OnCountdownType>,
+    ) -> CountdownHandle {
+        CountdownHandle { task: tokio::spawn(run(*self, offsets, callback)) }
+    }
+}
+
+/// Passed to the countdown callbacks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CountdownTick {
+    /// The offset the callback has been scheduled at.
+    pub offset: std::time::Duration,
+
+    /// The actual time remaining, which may be a bit less than `offset`.
+    pub time_remaining: std::time::Duration,
+    pub deadline: std::time::SystemTime,
+}
+
+/// Scheduled countdown callbacks, see [TerminationCountdown::schedule] and
+/// [Api::on_termination_countdown](crate::api::Api::on_termination_countdown).
+#[derive(Debug)]
+pub struct CountdownHandle {
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl CountdownHandle {
+    pub(crate) fn spawn(
+        api: crate::api::Api,
+        offsets: Vec<std::time::Duration>,
+        callback: Box<OnCountdownType>,
+    ) -> Self {
+        let task = async move {
+            if let Some(countdown) = wait_for_termination(&api).await {
+                run(countdown, offsets, callback).await;
+            }
+        };
+        Self { task: tokio::spawn(task) }
+    }
+
+    /// Whether all callbacks have run.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+impl Drop for CountdownHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Waits until GameLift has sent a termination time. Returns `None` if it
+/// never will.
+async fn wait_for_termination(api: &crate::api::Api) -> Option<TerminationCountdown> {
+    let mut notifications = api.subscribe_notifications();
+    loop {
+        if let Ok(countdown) = api.termination_countdown().await {
+            return Some(countdown);
+        }
+        match notifications.recv().await {
+            Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {}
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => return None,
+        }
+    }
+}
+
+async fn run(
+    countdown: TerminationCountdown,
+    mut offsets: Vec<std::time::Duration>,
+    callback: Box<OnCountdownType>,
+) {
+    offsets.sort_by(|a, b| b.cmp(a));
+    offsets.dedup();
+    let deadline = countdown.deadline_instant();
+
+    for offset in offsets {
+        let time_remaining = countdown.time_remaining();
+        if time_remaining < offset {
+            continue;
+        }
+        tokio::time::sleep_until(deadline.checked_sub(offset).unwrap_or(deadline)).await;
+        callback(CountdownTick {
+            offset,
+            time_remaining: countdown.time_remaining(),
+            deadline: countdown.deadline(),
+        })
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::termination::TerminationCountdown;
+
+    #[test]
+    fn from_termination_time_test() {
+        let countdown = TerminationCountdown::from_termination_time(1_700_000_000);
+        assert_eq!(
+            countdown.deadline(),
+            std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000)
+        );
+        assert_eq!(countdown.time_remaining(), std::time::Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn schedule_test() {
+        let countdown = TerminationCountdown::new(
+            std::time::SystemTime::now() + std::time::Duration::from_millis(300),
+        );
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let _handle = countdown.schedule(
+            vec![
+                std::time::Duration::from_millis(100),
+                std::time::Duration::from_secs(10),
+                std::time::Duration::from_millis(200),
+            ],
+            Box::new(move |tick| {
+                let _ = sender.send(tick);
+                Box::pin(async {})
+            }),
+        );
+
+        let first = receiver.recv().await.unwrap();
+        assert_eq!(first.offset, std::time::Duration::from_millis(200));
+        assert!(first.time_remaining <= first.offset);
+        assert_eq!(receiver.recv().await.unwrap().offset, std::time::Duration::from_millis(100));
+        assert!(receiver.recv().await.is_none());
+    }
+}
@@ -0,0 +1,406 @@
+//! An in-process stand-in for AuxProxy, the GameLift agent the SDK talks to.
+//! It is meant for deterministic end-to-end tests of game servers without
+//! GameLift Local or a real fleet.
+//!
+//! The mock serves the same contract as AuxProxy: protobuf requests over HTTP
+//! and JSON events over a WebSocket connection. Every request is recorded and
+//! can be inspected later, events are pushed to all connected SDK instances.
+
+use futures_util::{SinkExt, StreamExt};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+use tokio_tungstenite::tungstenite::Message;
+
+/// The default AuxProxy HTTP port.
+pub const DEFAULT_HTTP_PORT: u16 = 5758;
+/// The default AuxProxy WebSocket port.
+pub const DEFAULT_WEB_SOCKET_PORT: u16 = 5759;
+
+const TYPE_URL_PREFIX: &str = "type.googleapis.com/";
+
+/// A request received by [MockAuxProxy].
+#[derive(Debug, Clone)]
+pub struct ReceivedMessage {
+    /// The message name from the `gamelift-target` header, e.g.
+    /// `ProcessReady`.
+    pub message_type: String,
+    /// The value of the `gamelift-server-pid` header.
+    pub pid: Option<String>,
+    /// The protobuf-encoded message.
+    pub payload: Vec<u8>,
+}
+
+impl ReceivedMessage {
+    /// Decodes the payload as the given protobuf message.
+    pub fn decode<T>(&self) -> Result<T, protobuf::ProtobufError>
+    where
+        T: protobuf::Message,
+    {
+        T::parse_from_bytes(&self.payload)
+    }
+}
+
+/// A response [MockAuxProxy] sends back for a request type.
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl MockResponse {
+    /// A successful response with the given JSON body.
+    pub fn ok(body: impl Into<String>) -> Self {
+        Self { status: 200, body: body.into() }
+    }
+
+    /// A response with the given HTTP status and body.
+    pub fn with_status(status: u16, body: impl Into<String>) -> Self {
+        Self { status, body: body.into() }
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    received_messages: parking_lot::Mutex<Vec<ReceivedMessage>>,
+    responses: parking_lot::Mutex<std::collections::HashMap<String, MockResponse>>,
+    queued_responses: parking_lot::Mutex<
+        std::collections::HashMap<String, std::collections::VecDeque<MockResponse>>,
+    >,
+    web_socket_clients:
+        parking_lot::Mutex<Vec<tokio::sync::mpsc::UnboundedSender<Option<Message>>>>,
+    changed: tokio::sync::Notify,
+    connection_count: std::sync::atomic::AtomicUsize,
+}
+
+impl MockState {
+    fn record(&self, message: ReceivedMessage) {
+        self.received_messages.lock().push(message);
+        self.changed.notify_waiters();
+    }
+}
+
+/// An in-process AuxProxy stand-in. Stops serving when dropped.
+pub struct MockAuxProxy {
+    state: std::sync::Arc<MockState>,
+    http_port: u16,
+    web_socket_port: u16,
+    tasks: Vec<tokio::task::JoinHandle<()>>,
+}
+
+impl MockAuxProxy {
+    /// Starts the mock on the default AuxProxy ports.
+    pub async fn start() -> std::io::Result<Self> {
+        Self::start_on(DEFAULT_HTTP_PORT, DEFAULT_WEB_SOCKET_PORT).await
+    }
+
+    /// Starts the mock on the given ports. Port 0 picks a free port, use
+    /// [sdk_config](Self::sdk_config) to point the SDK at it.
+    pub async fn start_on(http_port: u16, web_socket_port: u16) -> std::io::Result<Self> {
+        let http_listener = tokio::net::TcpListener::bind(("127.0.0.1", http_port)).await?;
+        let web_socket_listener =
+            tokio::net::TcpListener::bind(("127.0.0.1", web_socket_port)).await?;
+        let http_port = http_listener.local_addr()?.port();
+        let web_socket_port = web_socket_listener.local_addr()?.port();
+
+        let state = std::sync::Arc::new(MockState::default());
+        let tasks = vec![
+            tokio::spawn(serve_http(http_listener, state.clone())),
+            tokio::spawn(serve_web_socket(web_socket_listener, state.clone())),
+        ];
+
+        Ok(Self { state, http_port, web_socket_port, tasks })
+    }
+
+    pub fn http_port(&self) -> u16 {
+        self.http_port
+    }
+
+    pub fn web_socket_port(&self) -> u16 {
+        self.web_socket_port
+    }
+
+    /// Returns the SDK configuration pointing at this mock.
+    pub fn sdk_config(&self) -> crate::sdk_config::SdkConfig {
+        crate::sdk_config::SdkConfig {
+            host: "127.0.0.1".to_string(),
+            http_port: self.http_port,
+            web_socket_port: self.web_socket_port,
+            ..Default::default()
+        }
+    }
+
+    /// Returns all requests received so far, in order.
+    pub fn received_messages(&self) -> Vec<ReceivedMessage> {
+        self.state.received_messages.lock().clone()
+    }
+
+    /// Returns all requests of the given type received so far, in order.
+    pub fn received_messages_of_type(&self, message_type: &str) -> Vec<ReceivedMessage> {
+        self.state
+            .received_messages
+            .lock()
+            .iter()
+            .filter(|message| message.message_type == message_type)
+            .cloned()
+            .collect()
+    }
+
+    /// Waits until `count` requests of the given type have been received and
+    /// returns the last of them. Returns `None` on timeout. Panics if `count`
+    /// is 0.
+    pub async fn wait_for_messages(
+        &self,
+        message_type: &str,
+        count: usize,
+        timeout: std::time::Duration,
+    ) -> Option<ReceivedMessage> {
+        assert!(count > 0, "Message counts start at 1");
+        tokio::time::timeout(timeout, async {
+            loop {
+                let changed = self.state.changed.notified();
+                let messages = self.received_messages_of_type(message_type);
+                if messages.len() >= count {
+                    return messages.into_iter().nth(count - 1);
+                }
+                changed.await;
+            }
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+
+    /// Waits for the first request of the given type.
+    pub async fn wait_for_message(
+        &self,
+        message_type: &str,
+        timeout: std::time::Duration,
+    ) -> Option<ReceivedMessage> {
+        self.wait_for_messages(message_type, 1, timeout).await
+    }
+
+    /// Returns the number of WebSocket connections accepted so far.
+    pub fn connection_count(&self) -> usize {
+        self.state.connection_count.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// Waits until `count` WebSocket connections have been accepted in total.
+    pub async fn wait_for_connections(&self, count: usize, timeout: std::time::Duration) -> bool {
+        tokio::time::timeout(timeout, async {
+            loop {
+                let changed = self.state.changed.notified();
+                if self.connection_count() >= count {
+                    return;
+                }
+                changed.await;
+            }
+        })
+        .await
+        .is_ok()
+    }
+
+    /// Sets the response for all subsequent requests of the given type. By
+    /// default every request gets an empty `200 OK` response.
+    pub fn set_response(&self, message_type: &str, response: MockResponse) {
+        self.state.responses.lock().insert(message_type.to_string(), response);
+    }
+
+    /// Queues a response for a single request of the given type, e.g. to
+    /// return consecutive pages. Queued responses take precedence over the one
+    /// set with [set_response](Self::set_response).
+    pub fn push_response(&self, message_type: &str, response: MockResponse) {
+        self.state
+            .queued_responses
+            .lock()
+            .entry(message_type.to_string())
+            .or_default()
+            .push_back(response);
+    }
+
+    /// Pushes an `ActivateGameSession` event to the connected SDK instances.
+    pub fn activate_game_session(
+        &self,
+        game_session: crate::protos::generated_with_pure::sdk::GameSession,
+    ) {
+        self.push_message(&crate::protos::generated_with_pure::sdk::ActivateGameSession {
+            gameSession: protobuf::SingularPtrField::some(game_session),
+            ..Default::default()
+        });
+    }
+
+    /// Pushes an `UpdateGameSession` event to the connected SDK instances.
+    pub fn update_game_session(
+        &self,
+        game_session: crate::protos::generated_with_pure::sdk::GameSession,
+        update_reason: &str,
+        backfill_ticket_id: &str,
+    ) {
+        self.push_message(&crate::protos::generated_with_pure::sdk::UpdateGameSession {
+            gameSession: protobuf::SingularPtrField::some(game_session),
+            updateReason: update_reason.to_string(),
+            backfillTicketId: backfill_ticket_id.to_string(),
+            ..Default::default()
+        });
+    }
+
+    /// Pushes a `TerminateProcess` event to the connected SDK instances.
+    pub fn terminate_process(&self, termination_time: i64) {
+        self.push_message(&crate::protos::generated_with_pure::sdk::TerminateProcess {
+            terminationTime: termination_time,
+            ..Default::default()
+        });
+    }
+
+    /// Wraps the message into an `AuxProxyToSdkEnvelope` and pushes it to the
+    /// connected SDK instances.
+    pub fn push_message<T>(&self, message: &T)
+    where
+        T: protobuf::Message + serde::Serialize,
+    {
+        let mut inner_message =
+            serde_json::to_value(message).expect("Protobuf messages are always serializable");
+        inner_message["@type"] = serde_json::Value::String(format!(
+            "{}{}",
+            TYPE_URL_PREFIX,
+            message.descriptor().full_name()
+        ));
+
+        self.push_raw(serde_json::json!({ "innerMessage": inner_message }).to_string());
+    }
+
+    /// Pushes an arbitrary text frame to the connected SDK instances.
+    pub fn push_raw(&self, text: String) {
+        self.state
+            .web_socket_clients
+            .lock()
+            .retain(|client| client.send(Some(Message::Text(text.clone()))).is_ok());
+    }
+
+    /// Closes all WebSocket connections, e.g. to simulate an AuxProxy restart.
+    pub fn disconnect_clients(&self) {
+        for client in self.state.web_socket_clients.lock().drain(..) {
+            let _ = client.send(None);
+        }
+    }
+}
+
+impl Drop for MockAuxProxy {
+    fn drop(&mut self) {
+        self.disconnect_clients();
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+async fn serve_http(listener: tokio::net::TcpListener, state: std::sync::Arc<MockState>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        let state = state.clone();
+        tokio::spawn(async move {
+            if let Err(error) = handle_http_connection(stream, &state).await {
+                log::debug!("Mock AuxProxy HTTP connection failed: {}", error);
+            }
+        });
+    }
+}
+
+async fn handle_http_connection(
+    stream: tokio::net::TcpStream,
+    state: &MockState,
+) -> std::io::Result<()> {
+    let mut stream = tokio::io::BufReader::new(stream);
+    loop {
+        let mut request_line = String::new();
+        if stream.read_line(&mut request_line).await? == 0 {
+            return Ok(());
+        }
+
+        let mut headers = std::collections::HashMap::new();
+        loop {
+            let mut header = String::new();
+            stream.read_line(&mut header).await?;
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+            }
+        }
+
+        let content_length =
+            headers.get("content-length").and_then(|value| value.parse().ok()).unwrap_or(0);
+        let mut payload = vec![0; content_length];
+        stream.read_exact(&mut payload).await?;
+
+        let message_type = headers.remove("gamelift-target").unwrap_or_default();
+        let queued_response = state
+            .queued_responses
+            .lock()
+            .get_mut(&message_type)
+            .and_then(|responses| responses.pop_front());
+        let response = queued_response.unwrap_or_else(|| {
+            state
+                .responses
+                .lock()
+                .get(&message_type)
+                .cloned()
+                .unwrap_or_else(|| MockResponse::ok(""))
+        });
+        state.record(ReceivedMessage {
+            message_type,
+            pid: headers.remove("gamelift-server-pid"),
+            payload,
+        });
+
+        let response = format!(
+            "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            response.status,
+            response.body.len(),
+            response.body
+        );
+        stream.get_mut().write_all(response.as_bytes()).await?;
+    }
+}
+
+async fn serve_web_socket(listener: tokio::net::TcpListener, state: std::sync::Arc<MockState>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        let state = state.clone();
+        // A client stalling in the handshake must not block the others.
+        tokio::spawn(async move {
+            let ws_stream = match tokio_tungstenite::accept_async(stream).await {
+                Ok(ws_stream) => ws_stream,
+                Err(error) => {
+                    log::debug!("Mock AuxProxy WebSocket handshake failed: {}", error);
+                    return;
+                }
+            };
+
+            let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+            state.web_socket_clients.lock().push(sender);
+            state.connection_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            state.changed.notify_waiters();
+
+            let (mut write, mut read) = ws_stream.split();
+            loop {
+                tokio::select! {
+                    outgoing = receiver.recv() => match outgoing {
+                        Some(Some(message)) => {
+                            if write.send(message).await.is_err() {
+                                return;
+                            }
+                        }
+                        _ => {
+                            let _ = write.send(Message::Close(None)).await;
+                            return;
+                        }
+                    },
+                    incoming = read.next() => {
+                        if !matches!(incoming, Some(Ok(_))) {
+                            return;
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
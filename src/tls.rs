@@ -0,0 +1,714 @@
+/// Signature schemes tried when checking that the private key belongs to the
+/// certificate, see [verify_key_matches].
+const KEY_CHECK_SCHEMES: &[rustls::SignatureScheme] = &[
+    rustls::SignatureScheme::ED25519,
+    rustls::SignatureScheme::ECDSA_NISTP256_SHA256,
+    rustls::SignatureScheme::ECDSA_NISTP384_SHA384,
+    rustls::SignatureScheme::RSA_PKCS1_SHA256,
+];
+
+/// Signature algorithms accepted when checking that the certificate chains to
+/// the root certificate, see [verify_chain].
+static CHAIN_SIGNATURE_ALGORITHMS: &[&webpki::SignatureAlgorithm] = &[
+    &webpki::ECDSA_P256_SHA256,
+    &webpki::ECDSA_P256_SHA384,
+    &webpki::ECDSA_P384_SHA256,
+    &webpki::ECDSA_P384_SHA384,
+    &webpki::ED25519,
+    &webpki::RSA_PKCS1_2048_8192_SHA256,
+    &webpki::RSA_PKCS1_2048_8192_SHA384,
+    &webpki::RSA_PKCS1_2048_8192_SHA512,
+    &webpki::RSA_PKCS1_3072_8192_SHA384,
+    &webpki::RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
+    &webpki::RSA_PSS_2048_8192_SHA384_LEGACY_KEY,
+    &webpki::RSA_PSS_2048_8192_SHA512_LEGACY_KEY,
+];
+
+pub type OnCertificateEventOutputType =
+    std::pin::Pin<Box<dyn std::future::Future<Output = ()> + std::marker::Send>>;
+pub type OnCertificateEventType = dyn Fn(CertificateEvent) -> OnCertificateEventOutputType
+    + std::marker::Send
+    + std::marker::Sync;
+
+struct LoadedCertificate {
+    source: crate::entity::GetInstanceCertificateResult,
+    certified_key: std::sync::Arc<rustls::sign::CertifiedKey>,
+    root_certificate: rustls::Certificate,
+    not_after: Option<std::time::SystemTime>,
+}
+
+impl LoadedCertificate {
+    fn load(
+        certificate: &crate::entity::GetInstanceCertificateResult,
+    ) -> Result<Self, crate::error::GameLiftErrorType> {
+        let leaf =
+            read_certificates(&certificate.certificate_path)?.into_iter().next().ok_or_else(
+                || invalid(format!("no certificate in {}", certificate.certificate_path)),
+            )?;
+        let root_certificate =
+            read_certificates(&certificate.root_certificate_path)?.into_iter().next().ok_or_else(
+                || invalid(format!("no certificate in {}", certificate.root_certificate_path)),
+            )?;
+        // The chain file may repeat the certificate and the root, which clients
+        // have to know anyway.
+        let mut certificate_chain = read_certificates(&certificate.certificate_chain_path)?;
+        certificate_chain.retain(|chain_certificate| {
+            chain_certificate != &leaf && chain_certificate != &root_certificate
+        });
+        certificate_chain.insert(0, leaf);
+        let private_key = read_private_key(&certificate.private_key_path)?;
+
+        let signing_key = rustls::sign::any_supported_type(&private_key)
+            .map_err(|_| invalid("the private key is not supported"))?;
+        verify_key_matches(&certificate_chain[0], signing_key.as_ref())?;
+        verify_hostname(&certificate_chain[0], &certificate.hostname)?;
+        verify_chain(&certificate_chain, &root_certificate)?;
+
+        Ok(Self {
+            source: certificate.clone(),
+            not_after: not_after(&certificate_chain[0].0),
+            certified_key: std::sync::Arc::new(rustls::sign::CertifiedKey::new(
+                certificate_chain,
+                signing_key,
+            )),
+            root_certificate,
+        })
+    }
+}
+
+/// Serves the currently loaded certificate, so that reloading it affects the
+/// server configurations handed out before.
+struct CertificateResolver {
+    loaded: std::sync::Arc<parking_lot::RwLock<LoadedCertificate>>,
+}
+
+impl rustls::server::ResolvesServerCert for CertificateResolver {
+    fn resolve(
+        &self,
+        _client_hello: rustls::server::ClientHello,
+    ) -> Option<std::sync::Arc<rustls::sign::CertifiedKey>> {
+        Some(self.loaded.read().certified_key.clone())
+    }
+}
+
+/// The TLS configuration of the fleet instance, loaded from the files of
+/// [get_instance_certificate](crate::api::Api::get_instance_certificate).
+/// Get it with [Api::instance_tls](crate::api::Api::instance_tls). All clones
+/// share the same certificate.
+///
+/// The certificate can be replaced with [reload](Self::reload), or
+/// automatically with
+/// [Api::watch_instance_tls](crate::api::Api::watch_instance_tls). The
+/// configurations and acceptors created before use the new certificate for
+/// new connections, while established connections are not affected.
+#[derive(Clone)]
+pub struct InstanceTls {
+    server_config: std::sync::Arc<rustls::ServerConfig>,
+    loaded: std::sync::Arc<parking_lot::RwLock<LoadedCertificate>>,
+}
+
+impl std::fmt::Debug for InstanceTls {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let loaded = self.loaded.read();
+        f.debug_struct("InstanceTls")
+            .field("hostname", &loaded.source.hostname)
+            .field("not_after", &loaded.not_after)
+            .finish()
+    }
+}
+
+impl InstanceTls {
+    /// Loads the PEM files and checks that the private key matches the
+    /// certificate, that the certificate is valid for the hostname and that
+    /// it chains to the root certificate.
+    /// Failures are reported as
+    /// [InvalidInstanceCertificate](crate::error::GameLiftErrorType::InvalidInstanceCertificate).
+    pub fn load(
+        certificate: &crate::entity::GetInstanceCertificateResult,
+    ) -> Result<Self, crate::error::GameLiftErrorType> {
+        let loaded =
+            std::sync::Arc::new(parking_lot::RwLock::new(LoadedCertificate::load(certificate)?));
+        let server_config = rustls::ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_cert_resolver(std::sync::Arc::new(CertificateResolver {
+                loaded: loaded.clone(),
+            }));
+        Ok(Self { server_config: std::sync::Arc::new(server_config), loaded })
+    }
+
+    /// Loads and validates the files like [load](Self::load) and replaces the
+    /// certificate. The previous certificate stays in use if this fails.
+    pub fn reload(
+        &self,
+        certificate: &crate::entity::GetInstanceCertificateResult,
+    ) -> Result<(), crate::error::GameLiftErrorType> {
+        let loaded = LoadedCertificate::load(certificate)?;
+        *self.loaded.write() = loaded;
+        Ok(())
+    }
+
+    /// A server configuration without client authentication. Clone it and
+    /// adjust e.g. the ALPN protocols if needed.
+    pub fn server_config(&self) -> std::sync::Arc<rustls::ServerConfig> {
+        self.server_config.clone()
+    }
+
+    /// Accepts TLS connections with [server_config](Self::server_config).
+    pub fn acceptor(&self) -> tokio_rustls::TlsAcceptor {
+        tokio_rustls::TlsAcceptor::from(self.server_config.clone())
+    }
+
+    /// The certificate followed by the intermediate certificates.
+    pub fn certificate_chain(&self) -> Vec<rustls::Certificate> {
+        self.loaded.read().certified_key.cert.clone()
+    }
+
+    /// The root certificate the clients have to trust.
+    pub fn root_certificate(&self) -> rustls::Certificate {
+        self.loaded.read().root_certificate.clone()
+    }
+
+    /// The hostname the certificate has been issued for.
+    pub fn hostname(&self) -> String {
+        self.loaded.read().source.hostname.clone()
+    }
+
+    /// When the certificate expires, or `None` if its validity could not be
+    /// read.
+    pub fn not_after(&self) -> Option<std::time::SystemTime> {
+        self.loaded.read().not_after
+    }
+
+    fn source(&self) -> crate::entity::GetInstanceCertificateResult {
+        self.loaded.read().source.clone()
+    }
+}
+
+/// What a certificate watcher has done, see
+/// [Api::watch_instance_tls](crate::api::Api::watch_instance_tls).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CertificateEvent {
+    /// A new certificate is in use. `not_after` is when it expires.
+    Reloaded { not_after: Option<std::time::SystemTime> },
+
+    /// The changed files could not be loaded, e.g. because the private key
+    /// does not match the certificate, so the previous certificate stays in
+    /// use. They are loaded again once they change.
+    ReloadFailed(crate::error::GameLiftErrorType),
+
+    /// The certificate expires within
+    /// [renew_before](CertificateWatcherConfig::renew_before) and no newer one
+    /// is available yet. Sent once per certificate.
+    ExpiringSoon { not_after: std::time::SystemTime },
+}
+
+pub struct CertificateWatcherConfig {
+    /// How often the files are checked for changes. Changed files are loaded
+    /// once they have stayed the same for a whole interval, so that a
+    /// certificate is not loaded while its files are still being written.
+    pub poll_interval: std::time::Duration,
+
+    /// How long before the expiry the watcher asks GameLift for a new
+    /// certificate.
+    pub renew_before: std::time::Duration,
+
+    /// Invoked for every [CertificateEvent]. Events are delivered in order.
+    pub on_event: Option<Box<OnCertificateEventType>>,
+}
+
+impl Default for CertificateWatcherConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: std::time::Duration::from_secs(60),
+            renew_before: std::time::Duration::from_secs(24 * 60 * 60),
+            on_event: None,
+        }
+    }
+}
+
+impl CertificateWatcherConfig {
+    async fn emit(&self, event: CertificateEvent) {
+        if let Some(on_event) = &self.on_event {
+            on_event(event).await;
+        }
+    }
+}
+
+/// A running certificate watcher. It stops when the handle is dropped.
+#[derive(Debug)]
+pub struct CertificateWatcherHandle {
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl CertificateWatcherHandle {
+    pub(crate) fn spawn(
+        api: crate::api::Api,
+        tls: InstanceTls,
+        config: CertificateWatcherConfig,
+    ) -> Self {
+        // Changes made after this call must be noticed, even before the task
+        // runs.
+        let source = tls.source();
+        let versions = SettledVersions::new(file_versions(&source));
+        Self { task: tokio::spawn(watch(api, tls, config, source, versions)) }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+impl Drop for CertificateWatcherHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn watch(
+    api: crate::api::Api,
+    tls: InstanceTls,
+    config: CertificateWatcherConfig,
+    mut source: crate::entity::GetInstanceCertificateResult,
+    mut versions: SettledVersions,
+) {
+    let mut expiring_reported = None;
+
+    loop {
+        tokio::time::sleep(config.poll_interval).await;
+
+        if expires_within(&tls, config.renew_before) {
+            match api.get_instance_certificate().await {
+                Ok(latest) if latest != source => {
+                    source = latest;
+                    // The new files are loaded once they are settled, too.
+                    versions = SettledVersions::new(Vec::new());
+                }
+                Ok(_) => {}
+                Err(error) => {
+                    log::warn!("Could not get the instance certificate from GameLift: {:?}", error)
+                }
+            }
+        }
+
+        if versions.settled(file_versions(&source)) {
+            match tls.reload(&source) {
+                Ok(()) => {
+                    log::info!("Reloaded the instance certificate.");
+                    config.emit(CertificateEvent::Reloaded { not_after: tls.not_after() }).await;
+                }
+                Err(error) => {
+                    log::warn!("Could not reload the instance certificate: {:?}", error);
+                    config.emit(CertificateEvent::ReloadFailed(error)).await;
+                }
+            }
+        }
+
+        if expires_within(&tls, config.renew_before) && expiring_reported != tls.not_after() {
+            expiring_reported = tls.not_after();
+            if let Some(not_after) = expiring_reported {
+                log::warn!("The instance certificate expires at {:?}.", not_after);
+                config.emit(CertificateEvent::ExpiringSoon { not_after }).await;
+            }
+        }
+    }
+}
+
+fn expires_within(tls: &InstanceTls, duration: std::time::Duration) -> bool {
+    tls.not_after().is_some_and(|not_after| not_after <= std::time::SystemTime::now() + duration)
+}
+
+type FileVersions = Vec<Option<(std::time::SystemTime, u64)>>;
+
+/// Tracks the [file_versions] of the loaded certificate and reports a change
+/// only once the new versions have been seen on two polls in a row.
+#[derive(Debug)]
+struct SettledVersions {
+    loaded: FileVersions,
+    pending: Option<FileVersions>,
+}
+
+impl SettledVersions {
+    fn new(loaded: FileVersions) -> Self {
+        Self { loaded, pending: None }
+    }
+
+    /// Returns `true` if the files have changed and are settled, then
+    /// `current` counts as loaded.
+    fn settled(&mut self, current: FileVersions) -> bool {
+        if current == self.loaded {
+            self.pending = None;
+            false
+        } else if self.pending.as_ref() == Some(&current) {
+            self.loaded = current;
+            self.pending = None;
+            true
+        } else {
+            self.pending = Some(current);
+            false
+        }
+    }
+}
+
+/// The modification time and length of every file, to detect changes.
+fn file_versions(certificate: &crate::entity::GetInstanceCertificateResult) -> FileVersions {
+    [
+        &certificate.certificate_path,
+        &certificate.private_key_path,
+        &certificate.certificate_chain_path,
+        &certificate.root_certificate_path,
+    ]
+    .into_iter()
+    .map(|path| {
+        let metadata = std::fs::metadata(path).ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
+    })
+    .collect()
+}
+
+fn invalid(reason: impl Into<String>) -> crate::error::GameLiftErrorType {
+    crate::error::GameLiftErrorType::InvalidInstanceCertificate(reason.into())
+}
+
+fn read_pem(path: &str) -> Result<Vec<rustls_pemfile::Item>, crate::error::GameLiftErrorType> {
+    let file = std::fs::File::open(path)
+        .map_err(|error| invalid(format!("cannot open {}: {}", path, error)))?;
+    rustls_pemfile::read_all(&mut std::io::BufReader::new(file))
+        .map_err(|error| invalid(format!("cannot read {}: {}", path, error)))
+}
+
+fn read_certificates(
+    path: &str,
+) -> Result<Vec<rustls::Certificate>, crate::error::GameLiftErrorType> {
+    Ok(read_pem(path)?
+        .into_iter()
+        .filter_map(|item| match item {
+            rustls_pemfile::Item::X509Certificate(der) => Some(rustls::Certificate(der)),
+            _ => None,
+        })
+        .collect())
+}
+
+fn read_private_key(path: &str) -> Result<rustls::PrivateKey, crate::error::GameLiftErrorType> {
+    read_pem(path)?
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(der)
+            | rustls_pemfile::Item::RSAKey(der)
+            | rustls_pemfile::Item::ECKey(der) => Some(rustls::PrivateKey(der)),
+            _ => None,
+        })
+        .ok_or_else(|| invalid(format!("no private key in {}", path)))
+}
+
+/// Signs a message with the private key and verifies the signature with the
+/// public key of the certificate.
+fn verify_key_matches(
+    certificate: &rustls::Certificate,
+    signing_key: &dyn rustls::sign::SigningKey,
+) -> Result<(), crate::error::GameLiftErrorType> {
+    const MESSAGE: &[u8] = b"GameLift instance certificate key check";
+
+    let signer = signing_key
+        .choose_scheme(KEY_CHECK_SCHEMES)
+        .ok_or_else(|| invalid("the private key is not supported"))?;
+    let algorithm = match signer.scheme() {
+        rustls::SignatureScheme::ED25519 => &webpki::ED25519,
+        rustls::SignatureScheme::ECDSA_NISTP256_SHA256 => &webpki::ECDSA_P256_SHA256,
+        rustls::SignatureScheme::ECDSA_NISTP384_SHA384 => &webpki::ECDSA_P384_SHA384,
+        _ => &webpki::RSA_PKCS1_2048_8192_SHA256,
+    };
+    let signature = signer.sign(MESSAGE).map_err(|error| invalid(error.to_string()))?;
+    webpki::EndEntityCert::try_from(certificate.0.as_slice())
+        .and_then(|certificate| certificate.verify_signature(algorithm, MESSAGE, &signature))
+        .map_err(|_| invalid("the private key does not match the certificate"))
+}
+
+fn verify_hostname(
+    certificate: &rustls::Certificate,
+    hostname: &str,
+) -> Result<(), crate::error::GameLiftErrorType> {
+    let dns_name = webpki::DnsNameRef::try_from_ascii_str(hostname)
+        .map_err(|_| invalid(format!("invalid hostname {:?}", hostname)))?;
+    webpki::EndEntityCert::try_from(certificate.0.as_slice())
+        .and_then(|certificate| certificate.verify_is_valid_for_dns_name(dns_name))
+        .map_err(|_| invalid(format!("the certificate is not valid for {}", hostname)))
+}
+
+/// Checks that the certificate, followed by the intermediate certificates,
+/// chains to the root certificate.
+fn verify_chain(
+    certificate_chain: &[rustls::Certificate],
+    root_certificate: &rustls::Certificate,
+) -> Result<(), crate::error::GameLiftErrorType> {
+    let trust_anchor = webpki::TrustAnchor::try_from_cert_der(&root_certificate.0)
+        .map_err(|error| invalid(format!("invalid root certificate: {}", error)))?;
+    let intermediates: Vec<&[u8]> =
+        certificate_chain[1..].iter().map(|certificate| certificate.0.as_slice()).collect();
+    let time = webpki::Time::try_from(std::time::SystemTime::now())
+        .map_err(|_| invalid("the system time is before the Unix epoch"))?;
+    webpki::EndEntityCert::try_from(certificate_chain[0].0.as_slice())
+        .and_then(|certificate| {
+            certificate.verify_is_valid_tls_server_cert(
+                CHAIN_SIGNATURE_ALGORITHMS,
+                &webpki::TlsServerTrustAnchors(&[trust_anchor]),
+                &intermediates,
+                time,
+            )
+        })
+        .map_err(|error| {
+            invalid(format!("the certificate does not chain to the root certificate: {}", error))
+        })
+}
+
+/// Reads the end of the validity period of a DER-encoded X.509 certificate:
+/// the second time of the validity, the fifth field of the TBSCertificate
+/// after the optional version.
+fn not_after(certificate: &[u8]) -> Option<std::time::SystemTime> {
+    let (_, certificate, _) = der_element(certificate)?;
+    let (_, mut fields, _) = der_element(certificate)?;
+    if fields.first() == Some(&0xa0) {
+        fields = der_element(fields)?.2;
+    }
+    for _ in 0..3 {
+        // The serial number, the signature algorithm and the issuer.
+        fields = der_element(fields)?.2;
+    }
+    let (_, validity, _) = der_element(fields)?;
+    let (_, _, validity) = der_element(validity)?;
+    let (tag, time, _) = der_element(validity)?;
+    let time = std::str::from_utf8(time).ok()?.strip_suffix('Z')?;
+    let (year, time) = match tag {
+        // UTCTime, YYMMDDHHMMSS
+        0x17 => {
+            let year: i64 = time.get(..2)?.parse().ok()?;
+            (if year < 50 { 2000 + year } else { 1900 + year }, time.get(2..)?)
+        }
+        // GeneralizedTime, YYYYMMDDHHMMSS
+        0x18 => (time.get(..4)?.parse().ok()?, time.get(4..)?),
+        _ => return None,
+    };
+    let field = |index: usize| -> Option<i64> { time.get(index..index + 2)?.parse().ok() };
+    let days = days_from_civil(year, field(0)?, field(2)?);
+    let seconds = days * 86400 + field(4)? * 3600 + field(6)? * 60 + field(8)?;
+    Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(u64::try_from(seconds).ok()?))
+}
+
+/// Splits a DER element off `input`. Returns its tag, its contents and the
+/// rest of the input.
+fn der_element(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, input) = input.split_first()?;
+    let (&length, mut input) = input.split_first()?;
+    let length = if length < 0x80 {
+        length as usize
+    } else {
+        let count = (length & 0x7f) as usize;
+        if count == 0 || count > std::mem::size_of::<usize>() || input.len() < count {
+            return None;
+        }
+        let (bytes, rest) = input.split_at(count);
+        input = rest;
+        bytes.iter().fold(0, |length, &byte| length << 8 | byte as usize)
+    };
+    if input.len() < length {
+        return None;
+    }
+    let (contents, rest) = input.split_at(length);
+    Some((tag, contents, rest))
+}
+
+/// Days since the Unix epoch of a date of the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tls::{CertificateEvent, CertificateWatcherConfig, InstanceTls, SettledVersions};
+
+    struct TestCertificate {
+        directory: std::path::PathBuf,
+        result: crate::entity::GetInstanceCertificateResult,
+    }
+
+    impl TestCertificate {
+        fn generate(name: &str, hostname: &str) -> Self {
+            let directory =
+                std::env::temp_dir().join(format!("gamelift-tls-{}-{}", name, std::process::id()));
+            std::fs::create_dir_all(&directory).unwrap();
+            let path = |file: &str| directory.join(file).to_string_lossy().into_owned();
+            let result = crate::entity::GetInstanceCertificateResult {
+                certificate_path: path("certificate.pem"),
+                private_key_path: path("private_key.pem"),
+                certificate_chain_path: path("certificate_chain.pem"),
+                hostname: hostname.to_string(),
+                root_certificate_path: path("root_certificate.pem"),
+            };
+            let certificate = Self { directory, result };
+            certificate.rotate();
+            certificate
+        }
+
+        /// Writes a new certificate issued by a new root.
+        fn rotate(&self) {
+            let mut ca_params = rcgen::CertificateParams::new(vec![]);
+            ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+            let ca = rcgen::Certificate::from_params(ca_params).unwrap();
+            let certificate =
+                rcgen::generate_simple_self_signed(vec!["*.gamelift.test".to_string()]).unwrap();
+
+            let certificate_pem = certificate.serialize_pem_with_signer(&ca).unwrap();
+            let root_pem = ca.serialize_pem().unwrap();
+            std::fs::write(&self.result.certificate_path, &certificate_pem).unwrap();
+            std::fs::write(&self.result.private_key_path, certificate.serialize_private_key_pem())
+                .unwrap();
+            std::fs::write(&self.result.certificate_chain_path, certificate_pem + &root_pem)
+                .unwrap();
+            std::fs::write(&self.result.root_certificate_path, root_pem).unwrap();
+        }
+    }
+
+    impl Drop for TestCertificate {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.directory);
+        }
+    }
+
+    #[tokio::test]
+    async fn load_test() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let certificate = TestCertificate::generate("load", "game-1.gamelift.test");
+        let tls = InstanceTls::load(&certificate.result).unwrap();
+        assert_eq!(tls.certificate_chain().len(), 1);
+        assert_eq!(tls.hostname(), "game-1.gamelift.test");
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(&tls.root_certificate()).unwrap();
+        let client_config = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let connector = tokio_rustls::TlsConnector::from(std::sync::Arc::new(client_config));
+        let (client, server) = tokio::io::duplex(4096);
+        let acceptor = tls.acceptor();
+        let server = tokio::spawn(async move {
+            let mut stream = acceptor.accept(server).await.unwrap();
+            stream.write_all(b"hello").await.unwrap();
+            stream.flush().await.unwrap();
+        });
+        let mut client = connector
+            .connect(rustls::ServerName::try_from("game-1.gamelift.test").unwrap(), client)
+            .await
+            .unwrap();
+        let mut message = [0; 5];
+        client.read_exact(&mut message).await.unwrap();
+        assert_eq!(&message, b"hello");
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn validation_test() {
+        let certificate = TestCertificate::generate("hostname", "game-1.example.com");
+        assert!(matches!(
+            InstanceTls::load(&certificate.result),
+            Err(crate::error::GameLiftErrorType::InvalidInstanceCertificate(reason))
+                if reason == "the certificate is not valid for game-1.example.com"
+        ));
+
+        let mut certificate = TestCertificate::generate("key", "game-1.gamelift.test");
+        let other = TestCertificate::generate("other-key", "game-1.gamelift.test");
+        certificate.result.private_key_path = other.result.private_key_path.clone();
+        assert!(matches!(
+            InstanceTls::load(&certificate.result),
+            Err(crate::error::GameLiftErrorType::InvalidInstanceCertificate(reason))
+                if reason == "the private key does not match the certificate"
+        ));
+
+        let mut certificate = TestCertificate::generate("root", "game-1.gamelift.test");
+        certificate.result.root_certificate_path = other.result.root_certificate_path.clone();
+        assert!(matches!(
+            InstanceTls::load(&certificate.result),
+            Err(crate::error::GameLiftErrorType::InvalidInstanceCertificate(reason))
+                if reason.starts_with("the certificate does not chain to the root certificate")
+        ));
+
+        certificate.result.root_certificate_path = "/nonexistent/root.pem".to_string();
+        assert!(InstanceTls::load(&certificate.result).is_err());
+    }
+
+    #[test]
+    fn not_after_test() {
+        for (year, day, seconds) in [(2030, 2, 1_893_542_400), (2060, 1, 2_840_140_800)] {
+            let mut params = rcgen::CertificateParams::new(vec!["gamelift.test".to_string()]);
+            params.not_after = rcgen::date_time_ymd(year, 1, day);
+            let certificate = rcgen::Certificate::from_params(params).unwrap();
+            assert_eq!(
+                crate::tls::not_after(&certificate.serialize_der().unwrap()),
+                Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(seconds))
+            );
+        }
+        assert_eq!(crate::tls::not_after(&[0x30, 0x03, 0x30]), None);
+    }
+
+    #[test]
+    fn settled_versions_test() {
+        let version = |length| Some((std::time::UNIX_EPOCH, length));
+        let mut versions = SettledVersions::new(vec![version(1), version(1)]);
+        assert!(!versions.settled(vec![version(1), version(1)]));
+
+        // The second file is written one poll after the first one.
+        assert!(!versions.settled(vec![version(2), version(1)]));
+        assert!(!versions.settled(vec![version(2), version(2)]));
+        assert!(versions.settled(vec![version(2), version(2)]));
+        assert!(!versions.settled(vec![version(2), version(2)]));
+
+        assert!(!versions.settled(vec![None, version(2)]));
+        assert!(!versions.settled(vec![version(2), version(2)]));
+    }
+
+    #[tokio::test]
+    async fn watch_test() {
+        let certificate = TestCertificate::generate("watch", "game-1.gamelift.test");
+        let tls = InstanceTls::load(&certificate.result).unwrap();
+        let first = tls.certificate_chain();
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let api = crate::api::Api::new(crate::sdk_config::SdkConfig::default());
+        let _watcher = api.watch_instance_tls(
+            &tls,
+            CertificateWatcherConfig {
+                poll_interval: std::time::Duration::from_millis(50),
+                renew_before: std::time::Duration::ZERO,
+                on_event: Some(Box::new(move |event| {
+                    let _ = sender.send(event);
+                    Box::pin(async {})
+                })),
+            },
+        );
+        let timeout = std::time::Duration::from_secs(5);
+
+        certificate.rotate();
+        let event = tokio::time::timeout(timeout, receiver.recv()).await.unwrap().unwrap();
+        assert!(matches!(event, CertificateEvent::Reloaded { not_after: Some(_) }));
+        let second = tls.certificate_chain();
+        assert_ne!(first, second);
+
+        let other = TestCertificate::generate("watch-other", "game-1.gamelift.test");
+        std::fs::copy(&other.result.private_key_path, &certificate.result.private_key_path)
+            .unwrap();
+        let event = tokio::time::timeout(timeout, receiver.recv()).await.unwrap().unwrap();
+        assert!(matches!(
+            event,
+            CertificateEvent::ReloadFailed(
+                crate::error::GameLiftErrorType::InvalidInstanceCertificate(_)
+            )
+        ));
+        assert_eq!(tls.certificate_chain(), second);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::error::GameLiftErrorType;
+
+/// Sends requests to GameLift using the protocol selected in
+/// [SdkConfig](crate::sdk_config::SdkConfig).
+pub enum Transport {
+    AuxProxy(crate::http_client::HttpClient),
+    WebSocket(crate::web_socket_client::WebSocketClient),
+}
+
+impl Transport {
+    pub fn new(config: &crate::sdk_config::SdkConfig) -> Self {
+        match &config.protocol {
+            crate::sdk_config::Protocol::AuxProxy => {
+                Self::AuxProxy(crate::http_client::HttpClient::new(config))
+            }
+            crate::sdk_config::Protocol::WebSocket(server_parameters) => Self::WebSocket(
+                crate::web_socket_client::WebSocketClient::new(config, server_parameters.clone()),
+            ),
+        }
+    }
+
+    /// The version of the Server SDK whose protocol is spoken.
+    pub fn sdk_version(&self) -> &'static str {
+        match self {
+            Self::AuxProxy(_) => crate::api::SDK_VERSION,
+            Self::WebSocket(_) => crate::web_socket_client::SDK_VERSION,
+        }
+    }
+
+    pub async fn process_ready(
+        &self,
+        port: i32,
+        log_paths_to_upload: Vec<String>,
+    ) -> Result<(), GameLiftErrorType> {
+        match self {
+            Self::AuxProxy(client) => client.process_ready(port, log_paths_to_upload).await,
+            Self::WebSocket(client) => client.process_ready(port, log_paths_to_upload).await,
+        }
+    }
+
+    pub async fn process_ending(&self) -> Result<(), GameLiftErrorType> {
+        match self {
+            Self::AuxProxy(client) => client.process_ending().await,
+            Self::WebSocket(client) => client.process_ending().await,
+        }
+    }
+
+    pub async fn report_health(&self, health_status: bool) -> Result<(), GameLiftErrorType> {
+        match self {
+            Self::AuxProxy(client) => client.report_health(health_status).await,
+            Self::WebSocket(client) => client.report_health(health_status).await,
+        }
+    }
+
+    pub async fn activate_game_session(
+        &self,
+        game_session_id: crate::entity::GameSessionId,
+    ) -> Result<(), GameLiftErrorType> {
+        match self {
+            Self::AuxProxy(client) => client.activate_game_session(game_session_id).await,
+            Self::WebSocket(client) => client.activate_game_session(game_session_id).await,
+        }
+    }
+
+    pub async fn terminate_game_session(
+        &self,
+        game_session_id: crate::entity::GameSessionId,
+    ) -> Result<(), GameLiftErrorType> {
+        match self {
+            Self::AuxProxy(client) => client.terminate_game_session(game_session_id).await,
+            // Server SDK 5.x has no such call, the process ends instead.
+            Self::WebSocket(_) => Err(GameLiftErrorType::UnsupportedOperation),
+        }
+    }
+
+    pub async fn update_player_session_creation_policy(
+        &self,
+        game_session_id: crate::entity::GameSessionId,
+        player_session_policy: crate::entity::PlayerSessionCreationPolicy,
+    ) -> Result<(), GameLiftErrorType> {
+        match self {
+            Self::AuxProxy(client) => {
+                client
+                    .update_player_session_creation_policy(game_session_id, player_session_policy)
+                    .await
+            }
+            Self::WebSocket(client) => {
+                client
+                    .update_player_session_creation_policy(game_session_id, player_session_policy)
+                    .await
+            }
+        }
+    }
+
+    pub async fn accept_player_session(
+        &self,
+        player_session_id: crate::entity::PlayerSessionId,
+        game_session_id: crate::entity::GameSessionId,
+    ) -> Result<(), GameLiftErrorType> {
+        match self {
+            Self::AuxProxy(client) => {
+                client.accept_player_session(player_session_id, game_session_id).await
+            }
+            Self::WebSocket(client) => {
+                client.accept_player_session(player_session_id, game_session_id).await
+            }
+        }
+    }
+
+    pub async fn remove_player_session(
+        &self,
+        player_session_id: crate::entity::PlayerSessionId,
+        game_session_id: crate::entity::GameSessionId,
+    ) -> Result<(), GameLiftErrorType> {
+        match self {
+            Self::AuxProxy(client) => {
+                client.remove_player_session(player_session_id, game_session_id).await
+            }
+            Self::WebSocket(client) => {
+                client.remove_player_session(player_session_id, game_session_id).await
+            }
+        }
+    }
+
+    pub async fn describe_player_sessions(
+        &self,
+        request: crate::entity::DescribePlayerSessionsRequest,
+    ) -> Result<crate::entity::DescribePlayerSessionsResult, GameLiftErrorType> {
+        match self {
+            Self::AuxProxy(client) => client.describe_player_sessions(request).await,
+            Self::WebSocket(client) => client.describe_player_sessions(request).await,
+        }
+    }
+
+    pub async fn backfill_matchmaking(
+        &self,
+        request: crate::entity::StartMatchBackfillRequest,
+    ) -> Result<crate::entity::StartMatchBackfillResult, GameLiftErrorType> {
+        match self {
+            Self::AuxProxy(client) => client.backfill_matchmaking(request).await,
+            Self::WebSocket(client) => client.backfill_matchmaking(request).await,
+        }
+    }
+
+    pub async fn stop_matchmaking(
+        &self,
+        request: crate::entity::StopMatchBackfillRequest,
+    ) -> Result<(), GameLiftErrorType> {
+        match self {
+            Self::AuxProxy(client) => client.stop_matchmaking(request).await,
+            Self::WebSocket(client) => client.stop_matchmaking(request).await,
+        }
+    }
+
+    pub async fn get_instance_certificate(
+        &self,
+    ) -> Result<crate::entity::GetInstanceCertificateResult, GameLiftErrorType> {
+        match self {
+            Self::AuxProxy(client) => client.get_instance_certificate().await,
+            Self::WebSocket(client) => client.get_instance_certificate().await,
+        }
+    }
+}
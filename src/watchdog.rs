@@ -0,0 +1,216 @@
+pub type OnStallOutputType =
+    std::pin::Pin<Box<dyn std::future::Future<Output = ()> + std::marker::Send>>;
+pub type OnStallType =
+    dyn Fn(StallInfo) -> OnStallOutputType + std::marker::Send + std::marker::Sync;
+
+/// Passed to [on_stall](WatchdogConfig::on_stall).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StallInfo {
+    pub name: String,
+
+    /// Time since the last [tick](Watchdog::tick).
+    pub since_last_tick: std::time::Duration,
+}
+
+pub struct WatchdogConfig {
+    /// The process is reported unhealthy if there has been no tick for this
+    /// long.
+    pub window: std::time::Duration,
+
+    /// Invoked once when a stall is detected. It is invoked again only after
+    /// the watchdog has been ticked in the meantime. It runs on its own task,
+    /// so it is not cancelled when the health check times out.
+    pub on_stall: Option<Box<OnStallType>>,
+}
+
+impl WatchdogConfig {
+    pub fn new(window: std::time::Duration) -> Self {
+        Self { window, on_stall: None }
+    }
+}
+
+struct WatchdogState {
+    name: String,
+    window: std::time::Duration,
+    last_tick: parking_lot::Mutex<std::time::Instant>,
+    stalled: std::sync::atomic::AtomicBool,
+    on_stall: Option<Box<OnStallType>>,
+}
+
+impl WatchdogState {
+    fn check(&self) -> crate::health::HealthCheckResult {
+        let since_last_tick = self.last_tick.lock().elapsed();
+        if since_last_tick <= self.window {
+            if self.stalled.swap(false, std::sync::atomic::Ordering::SeqCst) {
+                log::info!("The watchdog {} is being ticked again.", self.name);
+            }
+            return crate::health::HealthCheckResult::healthy();
+        }
+
+        if !self.stalled.swap(true, std::sync::atomic::Ordering::SeqCst) {
+            log::error!(
+                "The watchdog {} has not been ticked for {:?}, more than the window of {:?}. The \
+                 loop it watches is probably stuck.",
+                self.name,
+                since_last_tick,
+                self.window
+            );
+            if let Some(on_stall) = &self.on_stall {
+                tokio::spawn(on_stall(StallInfo { name: self.name.clone(), since_last_tick }));
+            }
+        }
+        crate::health::HealthCheckResult::unhealthy(format!("no tick for {:?}", since_last_tick))
+    }
+}
+
+/// Detects a stuck game loop: the loop calls [tick](Self::tick) on every
+/// iteration, and the process is reported unhealthy when no tick has arrived
+/// within the window. Get it with [Api::watchdog](crate::api::Api::watchdog).
+/// The watchdog is checked on every health report, see
+/// [HealthCheckConfig](crate::sdk_config::HealthCheckConfig).
+#[derive(Clone)]
+pub struct Watchdog {
+    state: std::sync::Arc<WatchdogState>,
+    registry: crate::health::HealthRegistry,
+    check: std::sync::Arc<crate::health::HealthCheckType>,
+}
+
+impl std::fmt::Debug for Watchdog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Watchdog")
+            .field("name", &self.state.name)
+            .field("window", &self.state.window)
+            .finish()
+    }
+}
+
+impl Watchdog {
+    /// Creates a watchdog and registers it as the health check `name`.
+    pub(crate) fn register(
+        registry: crate::health::HealthRegistry,
+        name: String,
+        config: WatchdogConfig,
+    ) -> Self {
+        let state = std::sync::Arc::new(WatchdogState {
+            name: name.clone(),
+            window: config.window,
+            last_tick: parking_lot::Mutex::new(std::time::Instant::now()),
+            stalled: Default::default(),
+            on_stall: config.on_stall,
+        });
+        let check_state = state.clone();
+        let check: std::sync::Arc<crate::health::HealthCheckType> =
+            std::sync::Arc::new(move || {
+                let result = check_state.check();
+                Box::pin(async move { result })
+            });
+        registry.register_shared(name, Default::default(), check.clone());
+        Self { state, registry, check }
+    }
+
+    /// Signals that the watched loop is alive. It is cheap and does not need
+    /// a tokio runtime, so it can be called from a dedicated game loop thread.
+    pub fn tick(&self) {
+        *self.state.last_tick.lock() = std::time::Instant::now();
+    }
+
+    /// Time since the last tick.
+    pub fn since_last_tick(&self) -> std::time::Duration {
+        self.state.last_tick.lock().elapsed()
+    }
+
+    /// Stops watching, e.g. when the game loop ends on purpose. A check that
+    /// has replaced the watchdog under the same name stays registered.
+    pub fn disarm(self) {
+        self.registry.unregister_check(&self.state.name, &self.check);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::watchdog::{Watchdog, WatchdogConfig};
+
+    const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+    #[tokio::test]
+    async fn watchdog_test() {
+        let registry = crate::health::HealthRegistry::default();
+        let stalls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let on_stall_stalls = stalls.clone();
+        let watchdog = Watchdog::register(
+            registry.clone(),
+            "game_loop".to_string(),
+            WatchdogConfig {
+                window: std::time::Duration::from_millis(20),
+                on_stall: Some(Box::new(move |info| {
+                    assert_eq!(info.name, "game_loop");
+                    on_stall_stalls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    Box::pin(async {})
+                })),
+            },
+        );
+
+        watchdog.tick();
+        assert!(registry.run_checks(TIMEOUT, async { true }).await.healthy);
+
+        tokio::time::sleep(std::time::Duration::from_millis(40)).await;
+        assert!(!registry.run_checks(TIMEOUT, async { true }).await.healthy);
+        assert!(!registry.run_checks(TIMEOUT, async { true }).await.healthy);
+        assert_eq!(stalls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        watchdog.tick();
+        assert!(registry.run_checks(TIMEOUT, async { true }).await.healthy);
+
+        tokio::time::sleep(std::time::Duration::from_millis(40)).await;
+        watchdog.disarm();
+        assert!(registry.run_checks(TIMEOUT, async { true }).await.healthy);
+        assert_eq!(stalls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn on_stall_test() {
+        let registry = crate::health::HealthRegistry::default();
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let _watchdog = Watchdog::register(
+            registry.clone(),
+            "game_loop".to_string(),
+            WatchdogConfig {
+                window: std::time::Duration::ZERO,
+                on_stall: Some(Box::new(move |info| {
+                    let sender = sender.clone();
+                    Box::pin(async move {
+                        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                        let _ = sender.send(info);
+                    })
+                })),
+            },
+        );
+
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        // The hook outlives the health check.
+        let snapshot =
+            registry.run_checks(std::time::Duration::from_millis(10), async { true }).await;
+        assert!(!snapshot.healthy);
+        let info = tokio::time::timeout(TIMEOUT, receiver.recv()).await.unwrap().unwrap();
+        assert_eq!(info.name, "game_loop");
+    }
+
+    #[tokio::test]
+    async fn disarm_test() {
+        let registry = crate::health::HealthRegistry::default();
+        let watchdog = Watchdog::register(
+            registry.clone(),
+            "game_loop".to_string(),
+            WatchdogConfig::new(std::time::Duration::from_secs(60)),
+        );
+        registry.register(
+            "game_loop",
+            Box::new(|| {
+                Box::pin(async { crate::health::HealthCheckResult::unhealthy("replaced") })
+            }),
+        );
+
+        watchdog.disarm();
+        assert!(!registry.run_checks(TIMEOUT, async { true }).await.healthy);
+    }
+}
@@ -0,0 +1,853 @@
+//! The Server SDK 5.x protocol: JSON action messages over a single
+//! authenticated WebSocket connection. Requests carry a `RequestId` the
+//! response is correlated by, events from GameLift carry no `RequestId`.
+
+use crate::{error::GameLiftErrorType, web_socket_listener::WebSocketStream};
+use futures_util::{stream::SplitSink, SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::Message;
+
+pub(crate) const SDK_VERSION: &str = "5.1.0";
+/// Used if [request_timeout](crate::sdk_config::SdkConfig::request_timeout)
+/// is not set. A request GameLift never answers would otherwise wait as long
+/// as the connection stays up.
+const DEFAULT_REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(20);
+const PID_KEY: &str = "pID";
+const SDK_VERSION_KEY: &str = "sdkVersion";
+const FLAVOR_KEY: &str = "sdkLanguage";
+const FLAVOR: &str = "Rust";
+const AUTH_TOKEN_KEY: &str = "Authorization";
+const COMPUTE_ID_KEY: &str = "ComputeId";
+const FLEET_ID_KEY: &str = "FleetId";
+
+type ResponseSender = tokio::sync::oneshot::Sender<Result<serde_json::Value, GameLiftErrorType>>;
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct WebSocketGameSession {
+    game_session_id: Option<String>,
+    game_session_name: Option<String>,
+    fleet_id: Option<String>,
+    maximum_player_session_count: i32,
+    port: i32,
+    ip_address: Option<String>,
+    game_session_data: Option<String>,
+    matchmaker_data: Option<String>,
+    game_properties: Option<std::collections::HashMap<String, String>>,
+    dns_name: Option<String>,
+}
+
+impl From<WebSocketGameSession> for crate::entity::GameSession {
+    fn from(source: WebSocketGameSession) -> Self {
+        Self {
+            game_session_id: source.game_session_id,
+            name: source.game_session_name,
+            fleet_id: source.fleet_id,
+            max_players: source.maximum_player_session_count,
+            port: source.port,
+            ip_address: source.ip_address,
+            game_session_data: source.game_session_data,
+            matchmaker_data: source.matchmaker_data,
+            game_properties: source.game_properties.map(|game_properties| {
+                game_properties
+                    .into_iter()
+                    .map(|(key, value)| crate::entity::GameProperty {
+                        key: Some(key),
+                        value: Some(value),
+                    })
+                    .collect()
+            }),
+            dns_name: source.dns_name,
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct WebSocketUpdateGameSession {
+    game_session: WebSocketGameSession,
+    update_reason: crate::entity::UpdateReason,
+    #[serde(default)]
+    backfill_ticket_id: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct WebSocketTerminateProcess {
+    termination_time: Option<i64>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct WebSocketRefreshConnection {
+    refresh_connection_endpoint: String,
+    auth_token: String,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct WebSocketPlayerSession {
+    player_id: Option<String>,
+    player_session_id: Option<String>,
+    game_session_id: Option<String>,
+    fleet_id: Option<String>,
+    ip_address: Option<String>,
+    player_data: Option<String>,
+    port: i32,
+    creation_time: i64,
+    termination_time: i64,
+    status: String,
+    dns_name: Option<String>,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct WebSocketDescribePlayerSessionsResponse {
+    next_token: Option<String>,
+    player_sessions: Vec<WebSocketPlayerSession>,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct WebSocketStartMatchBackfillResponse {
+    ticket_id: String,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct WebSocketGetComputeCertificateResponse {
+    certificate_path: String,
+    compute_name: String,
+}
+
+#[derive(Debug)]
+enum ReceivedMessageType {
+    Response {
+        request_id: String,
+        result: Result<serde_json::Value, GameLiftErrorType>,
+    },
+    CreateGameSession(crate::entity::GameSession),
+    UpdateGameSession {
+        game_session: crate::entity::GameSession,
+        update_reason: crate::entity::UpdateReason,
+        backfill_ticket_id: String,
+    },
+    TerminateProcess(Option<i64>),
+    RefreshConnection {
+        web_socket_url: String,
+        auth_token: String,
+    },
+}
+
+/// Events handed from the reading side of the connection to the task that
+/// invokes the callbacks, so the reader keeps receiving responses while a
+/// callback waits for one.
+enum Event {
+    StartGameSession(crate::entity::GameSession),
+    UpdateGameSession {
+        game_session: crate::entity::GameSession,
+        update_reason: crate::entity::UpdateReason,
+        backfill_ticket_id: String,
+    },
+    TerminateProcess(Option<i64>),
+    Disconnected,
+    Reconnected(u32),
+}
+
+struct Shared {
+    server_parameters: parking_lot::RwLock<crate::server_parameters::ServerParameters>,
+    request_timeout: std::time::Duration,
+    connect_timeout: Option<std::time::Duration>,
+    writer: tokio::sync::Mutex<Option<SplitSink<WebSocketStream, Message>>>,
+    pending_requests: parking_lot::Mutex<std::collections::HashMap<String, ResponseSender>>,
+    next_request_id: std::sync::atomic::AtomicU64,
+    handle: parking_lot::Mutex<Option<tokio::task::JoinHandle<()>>>,
+}
+
+pub struct WebSocketClient {
+    shared: std::sync::Arc<Shared>,
+}
+
+impl WebSocketClient {
+    pub fn new(
+        config: &crate::sdk_config::SdkConfig,
+        server_parameters: crate::server_parameters::ServerParameters,
+    ) -> Self {
+        Self {
+            shared: std::sync::Arc::new(Shared {
+                server_parameters: parking_lot::RwLock::new(server_parameters),
+                request_timeout: config.request_timeout.unwrap_or(DEFAULT_REQUEST_TIMEOUT),
+                connect_timeout: config.connect_timeout,
+                writer: Default::default(),
+                pending_requests: Default::default(),
+                next_request_id: Default::default(),
+                handle: Default::default(),
+            }),
+        }
+    }
+
+    pub async fn connect(
+        &self,
+        state: std::sync::Arc<crate::server_state::ServerStateInner>,
+    ) -> Result<(), GameLiftErrorType> {
+        let ws_stream = open_connection(&self.shared).await.map_err(|error| {
+            log::error!("Cannot connect to GameLift: {}", error);
+            GameLiftErrorType::LocalConnectionFailed
+        })?;
+        let (writer, reader) = ws_stream.split();
+        *self.shared.writer.lock().await = Some(writer);
+
+        let shared = self.shared.clone();
+        let (event_sender, event_receiver) = tokio::sync::mpsc::unbounded_channel();
+        *self.shared.handle.lock() = Some(tokio::spawn(async move {
+            tokio::join!(
+                run_connection(&shared, &state, reader, event_sender),
+                dispatch_events(&state, event_receiver)
+            );
+        }));
+
+        Ok(())
+    }
+
+    pub fn disconnect(&self) -> bool {
+        match self.shared.handle.lock().take() {
+            Some(handle) => {
+                handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    async fn send<T>(
+        &self,
+        action: &str,
+        mut message: serde_json::Value,
+    ) -> Result<T, GameLiftErrorType>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let request_id = self
+            .shared
+            .next_request_id
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+            .to_string();
+        message["Action"] = action.into();
+        message["RequestId"] = request_id.clone().into();
+        log::debug!("Sending {} request {}", action, request_id);
+
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        self.shared.pending_requests.lock().insert(request_id.clone(), sender);
+
+        let result = self.send_and_wait(message.to_string(), receiver).await;
+        self.shared.pending_requests.lock().remove(&request_id);
+
+        serde_json::from_value(result?).map_err(|error| {
+            GameLiftErrorType::MalformedResponse(format!(
+                "Cannot decode the {} response: {}",
+                action, error
+            ))
+        })
+    }
+
+    async fn send_and_wait(
+        &self,
+        message: String,
+        receiver: tokio::sync::oneshot::Receiver<Result<serde_json::Value, GameLiftErrorType>>,
+    ) -> Result<serde_json::Value, GameLiftErrorType> {
+        {
+            let mut writer = self.shared.writer.lock().await;
+            let writer = writer.as_mut().ok_or(GameLiftErrorType::NetworkNotInitialized)?;
+            writer.send(Message::Text(message)).await.map_err(|error| {
+                log::warn!("Cannot send a request to GameLift: {}", error);
+                GameLiftErrorType::ServiceCallFailed
+            })?;
+        }
+
+        let response = tokio::time::timeout(self.shared.request_timeout, receiver)
+            .await
+            .map_err(|_| GameLiftErrorType::ServiceCallFailed)?;
+        // The sender is dropped if the connection is lost before the response
+        // arrives.
+        response.map_err(|_| GameLiftErrorType::ServiceCallFailed)?
+    }
+
+    pub async fn process_ready(
+        &self,
+        port: i32,
+        log_paths_to_upload: Vec<String>,
+    ) -> Result<(), GameLiftErrorType> {
+        self.send::<serde_json::Value>(
+            "ActivateServerProcess",
+            serde_json::json!({
+                "SdkVersion": SDK_VERSION,
+                "SdkLanguage": FLAVOR,
+                "Port": port,
+                "LogPaths": log_paths_to_upload,
+            }),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    pub async fn process_ending(&self) -> Result<(), GameLiftErrorType> {
+        self.send::<serde_json::Value>("TerminateServerProcess", serde_json::json!({}))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn report_health(&self, health_status: bool) -> Result<(), GameLiftErrorType> {
+        self.send::<serde_json::Value>(
+            "HeartbeatServerProcess",
+            serde_json::json!({ "HealthStatus": health_status }),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    pub async fn activate_game_session(
+        &self,
+        game_session_id: crate::entity::GameSessionId,
+    ) -> Result<(), GameLiftErrorType> {
+        self.send::<serde_json::Value>(
+            "ActivateGameSession",
+            serde_json::json!({ "GameSessionId": game_session_id }),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    pub async fn update_player_session_creation_policy(
+        &self,
+        game_session_id: crate::entity::GameSessionId,
+        player_session_policy: crate::entity::PlayerSessionCreationPolicy,
+    ) -> Result<(), GameLiftErrorType> {
+        self.send::<serde_json::Value>(
+            "UpdatePlayerSessionCreationPolicy",
+            serde_json::json!({
+                "GameSessionId": game_session_id,
+                "PlayerSessionPolicy": player_session_policy_name(player_session_policy),
+            }),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    pub async fn accept_player_session(
+        &self,
+        player_session_id: crate::entity::PlayerSessionId,
+        game_session_id: crate::entity::GameSessionId,
+    ) -> Result<(), GameLiftErrorType> {
+        self.send::<serde_json::Value>(
+            "AcceptPlayerSession",
+            serde_json::json!({
+                "GameSessionId": game_session_id,
+                "PlayerSessionId": player_session_id,
+            }),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    pub async fn remove_player_session(
+        &self,
+        player_session_id: crate::entity::PlayerSessionId,
+        game_session_id: crate::entity::GameSessionId,
+    ) -> Result<(), GameLiftErrorType> {
+        self.send::<serde_json::Value>(
+            "RemovePlayerSession",
+            serde_json::json!({
+                "GameSessionId": game_session_id,
+                "PlayerSessionId": player_session_id,
+            }),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    pub async fn describe_player_sessions(
+        &self,
+        request: crate::entity::DescribePlayerSessionsRequest,
+    ) -> Result<crate::entity::DescribePlayerSessionsResult, GameLiftErrorType> {
+        let request = crate::mapper::describe_player_sessions_mapper(request);
+        let response: WebSocketDescribePlayerSessionsResponse = self
+            .send(
+                "DescribePlayerSessions",
+                serde_json::json!({
+                    "GameSessionId": request.gameSessionId,
+                    "PlayerId": request.playerId,
+                    "PlayerSessionId": request.playerSessionId,
+                    "PlayerSessionStatusFilter": request.playerSessionStatusFilter,
+                    "NextToken": request.nextToken,
+                    "Limit": request.limit,
+                }),
+            )
+            .await?;
+
+        describe_player_sessions_result_mapper(response)
+    }
+
+    pub async fn backfill_matchmaking(
+        &self,
+        request: crate::entity::StartMatchBackfillRequest,
+    ) -> Result<crate::entity::StartMatchBackfillResult, GameLiftErrorType> {
+        let request = crate::mapper::start_match_backfill_request_mapper(request)?;
+        let players: Vec<serde_json::Value> = request.players.iter().map(player_json).collect();
+        let response: WebSocketStartMatchBackfillResponse = self
+            .send(
+                "StartMatchBackfill",
+                serde_json::json!({
+                    "TicketId": request.ticketId,
+                    "GameSessionArn": request.gameSessionArn,
+                    "MatchmakingConfigurationArn": request.matchmakingConfigurationArn,
+                    "Players": players,
+                }),
+            )
+            .await?;
+
+        Ok(crate::entity::StartMatchBackfillResult { ticket_id: response.ticket_id })
+    }
+
+    pub async fn stop_matchmaking(
+        &self,
+        request: crate::entity::StopMatchBackfillRequest,
+    ) -> Result<(), GameLiftErrorType> {
+        let request = crate::mapper::stop_matchmaking_request_mapper(request)?;
+        self.send::<serde_json::Value>(
+            "StopMatchBackfill",
+            serde_json::json!({
+                "TicketId": request.ticketId,
+                "GameSessionArn": request.gameSessionArn,
+                "MatchmakingConfigurationArn": request.matchmakingConfigurationArn,
+            }),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    pub async fn get_instance_certificate(
+        &self,
+    ) -> Result<crate::entity::GetInstanceCertificateResult, GameLiftErrorType> {
+        let response: WebSocketGetComputeCertificateResponse =
+            self.send("GetComputeCertificate", serde_json::json!({})).await?;
+
+        Ok(crate::entity::GetInstanceCertificateResult {
+            certificate_path: response.certificate_path,
+            hostname: response.compute_name,
+            ..Default::default()
+        })
+    }
+}
+
+fn create_uri(
+    server_parameters: &crate::server_parameters::ServerParameters,
+) -> Result<String, String> {
+    let mut uri = reqwest::Url::parse(&server_parameters.web_socket_url)
+        .map_err(|error| error.to_string())?;
+    uri.query_pairs_mut()
+        .append_pair(PID_KEY, &server_parameters.process_id)
+        .append_pair(SDK_VERSION_KEY, SDK_VERSION)
+        .append_pair(FLAVOR_KEY, FLAVOR)
+        .append_pair(AUTH_TOKEN_KEY, &server_parameters.auth_token)
+        .append_pair(COMPUTE_ID_KEY, &server_parameters.host_id)
+        .append_pair(FLEET_ID_KEY, &server_parameters.fleet_id);
+
+    Ok(uri.to_string())
+}
+
+async fn open_connection(
+    shared: &Shared,
+) -> Result<WebSocketStream, tokio_tungstenite::tungstenite::Error> {
+    let connection_string = create_uri(&shared.server_parameters.read()).map_err(|error| {
+        tokio_tungstenite::tungstenite::Error::Url(
+            tokio_tungstenite::tungstenite::error::UrlError::UnableToConnect(error),
+        )
+    })?;
+    log::debug!("Connecting to GameLift at {}", shared.server_parameters.read().web_socket_url);
+    let connect = tokio_tungstenite::connect_async(connection_string);
+    let (ws_stream, _) = match shared.connect_timeout {
+        Some(connect_timeout) => {
+            tokio::time::timeout(connect_timeout, connect).await.map_err(|_| {
+                tokio_tungstenite::tungstenite::Error::Io(std::io::ErrorKind::TimedOut.into())
+            })??
+        }
+        None => connect.await?,
+    };
+    Ok(ws_stream)
+}
+
+/// Reads messages and keeps the connection alive: reconnects with backoff
+/// when it is lost and switches the endpoint on RefreshConnection. Stops once
+/// the process is ending.
+async fn run_connection(
+    shared: &Shared,
+    state: &crate::server_state::ServerStateInner,
+    reader: futures_util::stream::SplitStream<WebSocketStream>,
+    event_sender: tokio::sync::mpsc::UnboundedSender<Event>,
+) {
+    let mut reader = reader;
+    loop {
+        let refreshed = handle_messages(shared, &event_sender, reader).await;
+
+        shared.writer.lock().await.take();
+        shared.pending_requests.lock().clear();
+        if state.is_process_ending() {
+            log::debug!("The WebSocket connection to GameLift closed after ProcessEnding.");
+            state.notify(crate::notification::Notification::ConnectionClosed);
+            return;
+        }
+        let _ = event_sender.send(Event::Disconnected);
+
+        let mut delay = if refreshed {
+            log::info!("Switching to the refreshed GameLift endpoint.");
+            std::time::Duration::ZERO
+        } else {
+            log::warn!("Lost the WebSocket connection to GameLift. Reconnecting.");
+            crate::web_socket_listener::INITIAL_RECONNECT_DELAY
+        };
+        let mut attempts = 0;
+        reader = loop {
+            attempts += 1;
+            tokio::time::sleep(delay).await;
+            if state.is_process_ending() {
+                log::debug!("The process is ending. Stopped reconnecting to GameLift.");
+                state.notify(crate::notification::Notification::ConnectionClosed);
+                return;
+            }
+
+            match open_connection(shared).await {
+                Ok(ws_stream) => {
+                    log::info!("Reconnected to GameLift after {} attempt(s)", attempts);
+                    let (writer, reader) = ws_stream.split();
+                    *shared.writer.lock().await = Some(writer);
+                    let _ = event_sender.send(Event::Reconnected(attempts));
+                    break reader;
+                }
+                Err(error) => {
+                    log::warn!("Reconnection attempt {} failed: {}", attempts, error);
+                    delay = crate::web_socket_listener::next_reconnect_delay(std::cmp::max(
+                        delay,
+                        crate::web_socket_listener::INITIAL_RECONNECT_DELAY,
+                    ));
+                }
+            }
+        };
+    }
+}
+
+/// Handles incoming messages until the connection is closed or GameLift asks
+/// to reconnect. Returns `true` in the latter case.
+async fn handle_messages(
+    shared: &Shared,
+    event_sender: &tokio::sync::mpsc::UnboundedSender<Event>,
+    mut reader: futures_util::stream::SplitStream<WebSocketStream>,
+) -> bool {
+    while let Some(msg) = reader.next().await {
+        let message_text = match msg {
+            Ok(Message::Text(message_text)) => message_text,
+            Ok(Message::Close(frame)) => {
+                log::debug!("Socket disconnected. Message: {:?}", frame);
+                return false;
+            }
+            Ok(_) => continue,
+            Err(error) => {
+                log::warn!("WebSocket error: {}", error);
+                return false;
+            }
+        };
+
+        let event = match parse_message(&message_text) {
+            Ok(ReceivedMessageType::Response { request_id, result }) => {
+                match shared.pending_requests.lock().remove(&request_id) {
+                    Some(sender) => {
+                        let _ = sender.send(result);
+                    }
+                    None => log::debug!("Dropping a response to unknown request {}", request_id),
+                }
+                continue;
+            }
+            Ok(ReceivedMessageType::CreateGameSession(game_session)) => {
+                log::info!("Received CreateGameSession event");
+                Event::StartGameSession(game_session)
+            }
+            Ok(ReceivedMessageType::UpdateGameSession {
+                game_session,
+                update_reason,
+                backfill_ticket_id,
+            }) => {
+                log::info!("Received UpdateGameSession event");
+                Event::UpdateGameSession { game_session, update_reason, backfill_ticket_id }
+            }
+            Ok(ReceivedMessageType::TerminateProcess(termination_time)) => {
+                log::info!("Received TerminateProcess event");
+                Event::TerminateProcess(termination_time)
+            }
+            Ok(ReceivedMessageType::RefreshConnection { web_socket_url, auth_token }) => {
+                log::info!("Received RefreshConnection event");
+                let mut server_parameters = shared.server_parameters.write();
+                server_parameters.web_socket_url = web_socket_url;
+                server_parameters.auth_token = auth_token;
+                return true;
+            }
+            Err(error) => {
+                log::warn!("Dropping a message from GameLift: {:?}", error);
+                continue;
+            }
+        };
+        let _ = event_sender.send(event);
+    }
+
+    false
+}
+
+async fn dispatch_events(
+    state: &crate::server_state::ServerStateInner,
+    mut event_receiver: tokio::sync::mpsc::UnboundedReceiver<Event>,
+) {
+    while let Some(event) = event_receiver.recv().await {
+        match event {
+            Event::StartGameSession(game_session) => {
+                state.on_start_game_session(game_session).await;
+            }
+            Event::UpdateGameSession { game_session, update_reason, backfill_ticket_id } => {
+                state.on_update_game_session(game_session, update_reason, backfill_ticket_id).await;
+            }
+            Event::TerminateProcess(termination_time) => {
+                state.on_terminate_process(termination_time).await;
+            }
+            Event::Disconnected => {
+                state.notify(crate::notification::Notification::Disconnected);
+            }
+            Event::Reconnected(attempts) => {
+                state.on_reconnected(attempts).await;
+            }
+        }
+    }
+}
+
+fn parse_message(message_text: &str) -> Result<ReceivedMessageType, GameLiftErrorType> {
+    let unexpected = |reason: String| GameLiftErrorType::UnexpectedWebSocketMessage(reason);
+    let message: serde_json::Value = serde_json::from_str(message_text)
+        .map_err(|error| unexpected(format!("Malformed JSON ({}): {}", error, message_text)))?;
+    let decode = |action: &str, error: serde_json::Error| {
+        unexpected(format!("Cannot decode {} ({}): {}", action, error, message_text))
+    };
+
+    if let Some(request_id) = message.get("RequestId").and_then(|value| value.as_str()) {
+        if !request_id.is_empty() {
+            return Ok(ReceivedMessageType::Response {
+                request_id: request_id.to_string(),
+                result: response_result(message.clone()),
+            });
+        }
+    }
+
+    match message.get("Action").and_then(|value| value.as_str()) {
+        Some("CreateGameSession") => {
+            let game_session: WebSocketGameSession = serde_json::from_value(message)
+                .map_err(|error| decode("CreateGameSession", error))?;
+            if game_session.game_session_id.is_none() {
+                return Err(unexpected("CreateGameSession without GameSessionId".to_string()));
+            }
+            Ok(ReceivedMessageType::CreateGameSession(game_session.into()))
+        }
+        Some("UpdateGameSession") => {
+            let update: WebSocketUpdateGameSession = serde_json::from_value(message)
+                .map_err(|error| decode("UpdateGameSession", error))?;
+            Ok(ReceivedMessageType::UpdateGameSession {
+                game_session: update.game_session.into(),
+                update_reason: update.update_reason,
+                backfill_ticket_id: update.backfill_ticket_id,
+            })
+        }
+        Some("TerminateProcess") => {
+            let terminate: WebSocketTerminateProcess = serde_json::from_value(message)
+                .map_err(|error| decode("TerminateProcess", error))?;
+            Ok(ReceivedMessageType::TerminateProcess(terminate.termination_time))
+        }
+        Some("RefreshConnection") => {
+            let refresh: WebSocketRefreshConnection = serde_json::from_value(message)
+                .map_err(|error| decode("RefreshConnection", error))?;
+            Ok(ReceivedMessageType::RefreshConnection {
+                web_socket_url: refresh.refresh_connection_endpoint,
+                auth_token: refresh.auth_token,
+            })
+        }
+        Some(action) => Err(unexpected(format!("Unknown action: {}", action))),
+        None => Err(unexpected("The message does not contain Action".to_string())),
+    }
+}
+
+fn response_result(message: serde_json::Value) -> Result<serde_json::Value, GameLiftErrorType> {
+    let status_code = message.get("StatusCode").and_then(|value| value.as_u64()).unwrap_or(200);
+    let error_message = || {
+        message.get("ErrorMessage").and_then(|value| value.as_str()).unwrap_or_default().to_string()
+    };
+
+    match status_code {
+        400..=499 => Err(GameLiftErrorType::BadRequest(error_message())),
+        500..=599 => Err(GameLiftErrorType::InternalServiceError(error_message())),
+        _ => Ok(message),
+    }
+}
+
+fn player_session_policy_name(policy: crate::entity::PlayerSessionCreationPolicy) -> &'static str {
+    match policy {
+        crate::entity::PlayerSessionCreationPolicy::NotSet => "NOT_SET",
+        crate::entity::PlayerSessionCreationPolicy::AcceptAll => "ACCEPT_ALL",
+        crate::entity::PlayerSessionCreationPolicy::DenyAll => "DENY_ALL",
+    }
+}
+
+fn player_json(player: &crate::protos::generated_with_pure::sdk::Player) -> serde_json::Value {
+    let player_attributes: serde_json::Map<String, serde_json::Value> = player
+        .playerAttributes
+        .iter()
+        .map(|(name, value)| {
+            let value = match value.field_type {
+                1 => serde_json::json!({ "AttrType": "STRING", "S": value.S }),
+                2 => serde_json::json!({ "AttrType": "DOUBLE", "N": value.N }),
+                3 => serde_json::json!({ "AttrType": "STRING_LIST", "SL": value.SL.to_vec() }),
+                _ => serde_json::json!({ "AttrType": "STRING_DOUBLE_MAP", "SDM": value.SDM }),
+            };
+            (name.clone(), value)
+        })
+        .collect();
+
+    serde_json::json!({
+        "PlayerId": player.playerId,
+        "Team": player.team,
+        "PlayerAttributes": player_attributes,
+        "LatencyInMS": player.latencyInMs,
+    })
+}
+
+fn describe_player_sessions_result_mapper(
+    source: WebSocketDescribePlayerSessionsResponse,
+) -> Result<crate::entity::DescribePlayerSessionsResult, GameLiftErrorType> {
+    use std::str::FromStr;
+    let mut result = crate::entity::DescribePlayerSessionsResult {
+        player_sessions: vec![],
+        next_token: source.next_token.unwrap_or_default(),
+    };
+
+    for player_session in source.player_sessions {
+        let status =
+            crate::entity::PlayerSessionStatus::from_str(&player_session.status).map_err(|_| {
+                GameLiftErrorType::MalformedResponse(format!(
+                    "Unknown player session status: {}",
+                    player_session.status
+                ))
+            })?;
+        result.player_sessions.push(crate::entity::PlayerSession {
+            player_id: player_session.player_id,
+            player_session_id: player_session.player_session_id,
+            game_session_id: player_session.game_session_id,
+            fleet_id: player_session.fleet_id,
+            ip_address: player_session.ip_address,
+            player_data: player_session.player_data,
+            port: player_session.port,
+            creation_time: player_session.creation_time,
+            termination_time: player_session.termination_time,
+            status,
+            dns_name: player_session.dns_name,
+        });
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::GameLiftErrorType,
+        web_socket_client::{
+            create_uri, parse_message, ReceivedMessageType, WebSocketClient,
+            DEFAULT_REQUEST_TIMEOUT,
+        },
+    };
+
+    #[test]
+    fn request_timeout_test() {
+        let config = crate::sdk_config::SdkConfig::default();
+        let client = WebSocketClient::new(&config, Default::default());
+        assert_eq!(client.shared.request_timeout, DEFAULT_REQUEST_TIMEOUT);
+
+        let config = crate::sdk_config::SdkConfig {
+            request_timeout: Some(std::time::Duration::from_secs(3)),
+            ..config
+        };
+        let client = WebSocketClient::new(&config, Default::default());
+        assert_eq!(client.shared.request_timeout, std::time::Duration::from_secs(3));
+    }
+
+    #[test]
+    fn create_uri_test() {
+        let server_parameters = crate::server_parameters::ServerParameters {
+            web_socket_url: "wss://us-west-2.api.amazongamelift.com".to_string(),
+            process_id: "process-1".to_string(),
+            host_id: "compute-1".to_string(),
+            fleet_id: "fleet-1".to_string(),
+            auth_token: "token/with+symbols".to_string(),
+        };
+
+        assert_eq!(
+            create_uri(&server_parameters).unwrap(),
+            "wss://us-west-2.api.amazongamelift.com/?pID=process-1&sdkVersion=5.1.0&\
+             sdkLanguage=Rust&Authorization=token%2Fwith%2Bsymbols&ComputeId=compute-1&\
+             FleetId=fleet-1"
+        );
+    }
+
+    #[test]
+    fn parse_create_game_session_test() {
+        let message = serde_json::json!({
+            "Action": "CreateGameSession",
+            "GameSessionId": "gsess-1",
+            "MaximumPlayerSessionCount": 8,
+            "GameProperties": { "mode": "ctf" }
+        });
+
+        match parse_message(&message.to_string()).unwrap() {
+            ReceivedMessageType::CreateGameSession(game_session) => {
+                assert_eq!(game_session.game_session_id.as_deref(), Some("gsess-1"));
+                assert_eq!(game_session.max_players, 8);
+                assert_eq!(game_session.game_properties.unwrap()[0].value.as_deref(), Some("ctf"));
+            }
+            message => panic!("Unexpected message: {:?}", message),
+        }
+    }
+
+    #[test]
+    fn parse_response_test() {
+        let message = serde_json::json!({
+            "Action": "AcceptPlayerSession",
+            "RequestId": "7",
+            "StatusCode": 400,
+            "ErrorMessage": "Invalid player session"
+        });
+
+        assert!(matches!(
+            parse_message(&message.to_string()).unwrap(),
+            ReceivedMessageType::Response {
+                request_id,
+                result: Err(GameLiftErrorType::BadRequest(error_message))
+            } if request_id == "7" && error_message == "Invalid player session"
+        ));
+    }
+
+    #[test]
+    fn parse_unexpected_message_test() {
+        assert!(matches!(
+            parse_message(r#"{"Action": "Unknown"}"#),
+            Err(GameLiftErrorType::UnexpectedWebSocketMessage(_))
+        ));
+        assert!(matches!(
+            parse_message(r#"{"Action": "CreateGameSession"}"#),
+            Err(GameLiftErrorType::UnexpectedWebSocketMessage(_))
+        ));
+        assert!(matches!(
+            parse_message("not a json"),
+            Err(GameLiftErrorType::UnexpectedWebSocketMessage(_))
+        ));
+    }
+}
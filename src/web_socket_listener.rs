@@ -1,22 +1,28 @@
 use futures_util::StreamExt;
+use tokio_tungstenite::tungstenite::Message;
 
-const HOSTNAME: &str = "127.0.0.1";
-const PORT: i32 = 5759;
 const PID_KEY: &str = "pID";
 const SDK_VERSION_KEY: &str = "sdkVersion";
 const FLAVOR_KEY: &str = "sdkLanguage";
 const FLAVOR: &str = "Rust";
+pub(crate) const INITIAL_RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(30);
+
+pub(crate) type WebSocketStream =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
 
 pub struct WebSocketListener {
     handle: Option<tokio::task::JoinHandle<()>>,
-    state: std::sync::Arc<tokio::sync::RwLock<crate::server_state::ServerStateInner>>,
+    state: std::sync::Arc<crate::server_state::ServerStateInner>,
+    config: crate::sdk_config::SdkConfig,
 }
 
 impl WebSocketListener {
     pub fn new(
-        state: std::sync::Arc<tokio::sync::RwLock<crate::server_state::ServerStateInner>>,
+        state: std::sync::Arc<crate::server_state::ServerStateInner>,
+        config: crate::sdk_config::SdkConfig,
     ) -> Self {
-        Self { handle: None, state }
+        Self { handle: None, state, config }
     }
 
     pub fn disconnect(&self) -> bool {
@@ -36,81 +42,168 @@ impl WebSocketListener {
     }
 
     async fn perform_connect(&mut self) -> Result<(), tokio_tungstenite::tungstenite::Error> {
-        let connection_string = Self::create_uri();
-        log::debug!("AWS GameLift Server WebSocket connection string: {}", connection_string);
-        let (mut ws_stream, _) = tokio_tungstenite::connect_async(connection_string).await?;
+        let ws_stream = Self::open_connection(&self.config).await?;
 
         let callback_handler = self.state.clone();
+        let config = self.config.clone();
         self.handle = Some(tokio::spawn(async move {
-            while let Some(msg) = ws_stream.next().await {
-                let msg = msg.unwrap();
-                if msg.is_text() {
-                    let message_text = msg.into_text().unwrap();
-                    let v: serde_json::Value = serde_json::from_str(message_text.as_str()).unwrap();
-                    let message_type =
-                        get_inner_message_type(&v).expect("Unexpected received message");
-
-                    match message_type {
-                        ReceivedMessageType::ActivateGameSession(message) => {
-                            log::info!("Received ActivateGameSession event");
-                            callback_handler
-                                .read()
-                                .await
-                                .on_start_game_session(message.game_session)
-                                .await;
-                        }
-                        ReceivedMessageType::UpdateGameSession(message) => {
-                            log::info!("Received UpdateGameSession event");
-
-                            let game_session = message.game_session.unwrap();
-                            let update_reason = message.update_reason;
-                            callback_handler
-                                .read()
-                                .await
-                                .on_update_game_session(
-                                    game_session,
-                                    update_reason,
-                                    message.backfill_ticket_id,
-                                )
-                                .await;
-                        }
-                        ReceivedMessageType::TerminateProcess(message) => {
-                            log::info!("Received TerminateProcess event");
-
-                            callback_handler
-                                .read()
-                                .await
-                                .on_terminate_process(message.termination_time.unwrap())
-                                .await;
-                        }
-                    }
-                } else if msg.is_close() {
-                    log::debug!("Socket disconnected. Message: {}", msg);
+            let mut ws_stream = ws_stream;
+            loop {
+                Self::handle_messages(&callback_handler, ws_stream).await;
+                if callback_handler.is_process_ending() {
+                    log::debug!("The WebSocket connection to AuxProxy closed after ProcessEnding.");
+                    callback_handler.notify(crate::notification::Notification::ConnectionClosed);
+                    return;
                 }
+
+                log::warn!("Lost the WebSocket connection to AuxProxy. Reconnecting.");
+                callback_handler.notify(crate::notification::Notification::Disconnected);
+                ws_stream = match Self::reconnect(&callback_handler, &config).await {
+                    Some(ws_stream) => ws_stream,
+                    None => return,
+                };
             }
         }));
 
         Ok(())
     }
 
-    fn create_uri() -> String {
+    async fn open_connection(
+        config: &crate::sdk_config::SdkConfig,
+    ) -> Result<WebSocketStream, tokio_tungstenite::tungstenite::Error> {
+        let connection_string = Self::create_uri(config);
+        log::debug!("AWS GameLift Server WebSocket connection string: {}", connection_string);
+        let connect = tokio_tungstenite::connect_async(connection_string);
+        let (ws_stream, _) = match config.connect_timeout {
+            Some(connect_timeout) => {
+                tokio::time::timeout(connect_timeout, connect).await.map_err(|_| {
+                    tokio_tungstenite::tungstenite::Error::Io(std::io::ErrorKind::TimedOut.into())
+                })??
+            }
+            None => connect.await?,
+        };
+        Ok(ws_stream)
+    }
+
+    /// Reconnects with backoff. Gives up and returns `None` once the process
+    /// is ending, since GameLift sends it no more events.
+    async fn reconnect(
+        callback_handler: &crate::server_state::ServerStateInner,
+        config: &crate::sdk_config::SdkConfig,
+    ) -> Option<WebSocketStream> {
+        let mut delay = INITIAL_RECONNECT_DELAY;
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            tokio::time::sleep(delay).await;
+            if callback_handler.is_process_ending() {
+                log::debug!("The process is ending. Stopped reconnecting to AuxProxy.");
+                callback_handler.notify(crate::notification::Notification::ConnectionClosed);
+                return None;
+            }
+
+            match Self::open_connection(config).await {
+                Ok(ws_stream) => {
+                    log::info!("Reconnected to AuxProxy after {} attempt(s)", attempts);
+                    callback_handler.on_reconnected(attempts).await;
+                    return Some(ws_stream);
+                }
+                Err(error) => {
+                    log::warn!("Reconnection attempt {} failed: {}", attempts, error);
+                    delay = next_reconnect_delay(delay);
+                }
+            }
+        }
+    }
+
+    /// Dispatches incoming events until the connection is closed or broken.
+    async fn handle_messages(
+        callback_handler: &crate::server_state::ServerStateInner,
+        mut ws_stream: WebSocketStream,
+    ) {
+        while let Some(msg) = ws_stream.next().await {
+            let msg = match msg {
+                Ok(msg) => msg,
+                Err(error) => {
+                    log::warn!("WebSocket error: {}", error);
+                    return;
+                }
+            };
+            match msg {
+                Message::Text(message_text) => match parse_message(&message_text) {
+                    Ok(message_type) => {
+                        Self::dispatch_message(callback_handler, message_type).await;
+                    }
+                    Err(error) => {
+                        log::warn!("Dropping a message from AuxProxy: {:?}", error);
+                    }
+                },
+                Message::Close(frame) => {
+                    log::debug!("Socket disconnected. Message: {:?}", frame);
+                    return;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    async fn dispatch_message(
+        callback_handler: &crate::server_state::ServerStateInner,
+        message_type: ReceivedMessageType,
+    ) {
+        match message_type {
+            ReceivedMessageType::ActivateGameSession(message) => {
+                log::info!("Received ActivateGameSession event");
+                callback_handler.on_start_game_session(message.game_session).await;
+            }
+            ReceivedMessageType::UpdateGameSession {
+                game_session,
+                update_reason,
+                backfill_ticket_id,
+            } => {
+                log::info!("Received UpdateGameSession event");
+                callback_handler
+                    .on_update_game_session(game_session, update_reason, backfill_ticket_id)
+                    .await;
+            }
+            ReceivedMessageType::TerminateProcess(message) => {
+                log::info!("Received TerminateProcess event");
+                if message.termination_time.is_none() {
+                    log::warn!("TerminateProcess event does not contain the termination time");
+                }
+
+                callback_handler.on_terminate_process(message.termination_time).await;
+            }
+        }
+    }
+
+    fn create_uri(config: &crate::sdk_config::SdkConfig) -> String {
         let query_string = format!(
             "{}={}&{}={}&{}={}",
             PID_KEY,
-            std::process::id(),
+            config.pid,
             SDK_VERSION_KEY,
             crate::api::SDK_VERSION,
             FLAVOR_KEY,
             FLAVOR
         );
 
-        format!("ws://{}:{}?{}", HOSTNAME, PORT, query_string)
+        format!("{}?{}", config.web_socket_uri(), query_string)
     }
 }
 
+pub(crate) fn next_reconnect_delay(delay: std::time::Duration) -> std::time::Duration {
+    std::cmp::min(delay * 2, MAX_RECONNECT_DELAY)
+}
+
+#[derive(Debug)]
 enum ReceivedMessageType {
     ActivateGameSession(crate::entity::ActivateGameSession),
-    UpdateGameSession(crate::entity::UpdateGameSession),
+    UpdateGameSession {
+        game_session: crate::entity::GameSession,
+        update_reason: crate::entity::UpdateReason,
+        backfill_ticket_id: String,
+    },
     TerminateProcess(crate::entity::TerminateProcess),
 }
 
@@ -129,40 +222,171 @@ fn remove_type_info_from_json(source: &serde_json::Value) -> serde_json::Value {
     }
 }
 
+fn parse_message(
+    message_text: &str,
+) -> Result<ReceivedMessageType, crate::error::GameLiftErrorType> {
+    let v: serde_json::Value = serde_json::from_str(message_text).map_err(|error| {
+        crate::error::GameLiftErrorType::UnexpectedWebSocketMessage(format!(
+            "Malformed JSON ({}): {}",
+            error, message_text
+        ))
+    })?;
+    get_inner_message_type(&v)
+}
+
+fn unpack_inner_message<T>(
+    inner_message: &serde_json::Value,
+) -> Result<T, crate::error::GameLiftErrorType>
+where
+    T: serde::de::DeserializeOwned,
+{
+    serde_json::from_value(remove_type_info_from_json(inner_message)).map_err(|error| {
+        crate::error::GameLiftErrorType::UnexpectedWebSocketMessage(format!(
+            "Cannot decode {}: {}",
+            std::any::type_name::<T>(),
+            error
+        ))
+    })
+}
+
 fn get_inner_message_type(
     v: &serde_json::Value,
 ) -> Result<ReceivedMessageType, crate::error::GameLiftErrorType> {
-    if let Some(inner_message) = v.get("innerMessage") {
-        if let Some(message_type) = inner_message.get("@type") {
-            if let Some(message_type) = message_type.as_str() {
-                match message_type {
-                    "type.googleapis.com/com.amazon.whitewater.auxproxy.pbuffer.\
-                     ActivateGameSession" => {
-                        let unpack_result: crate::entity::ActivateGameSession =
-                            serde_json::from_value(remove_type_info_from_json(inner_message))
-                                .unwrap();
-                        return Ok(ReceivedMessageType::ActivateGameSession(unpack_result));
-                    }
-                    "type.googleapis.com/com.amazon.whitewater.auxproxy.pbuffer.\
-                     UpdateGameSession" => {
-                        let unpack_result: crate::entity::UpdateGameSession =
-                            serde_json::from_value(remove_type_info_from_json(inner_message))
-                                .unwrap();
-                        return Ok(ReceivedMessageType::UpdateGameSession(unpack_result));
-                    }
-                    "type.googleapis.com/com.amazon.whitewater.auxproxy.pbuffer.\
-                     TerminateProcess" => {
-                        let unpack_result: crate::entity::TerminateProcess =
-                            serde_json::from_value(remove_type_info_from_json(inner_message))
-                                .unwrap();
-                        return Ok(ReceivedMessageType::TerminateProcess(unpack_result));
-                    }
+    let inner_message = v.get("innerMessage").ok_or_else(|| {
+        crate::error::GameLiftErrorType::UnexpectedWebSocketMessage(
+            "The message does not contain innerMessage".to_string(),
+        )
+    })?;
+    let message_type = inner_message.get("@type").and_then(|message_type| message_type.as_str());
 
-                    _ => {}
-                };
+    match message_type {
+        Some("type.googleapis.com/com.amazon.whitewater.auxproxy.pbuffer.ActivateGameSession") => {
+            let unpack_result: crate::entity::ActivateGameSession =
+                unpack_inner_message(inner_message)?;
+            if unpack_result.game_session.game_session_id.is_none() {
+                return Err(crate::error::GameLiftErrorType::UnexpectedWebSocketMessage(
+                    "ActivateGameSession without gameSessionId".to_string(),
+                ));
             }
+            Ok(ReceivedMessageType::ActivateGameSession(unpack_result))
+        }
+        Some("type.googleapis.com/com.amazon.whitewater.auxproxy.pbuffer.UpdateGameSession") => {
+            let unpack_result: crate::entity::UpdateGameSession =
+                unpack_inner_message(inner_message)?;
+            let game_session = unpack_result.game_session.ok_or_else(|| {
+                crate::error::GameLiftErrorType::UnexpectedWebSocketMessage(
+                    "UpdateGameSession without gameSession".to_string(),
+                )
+            })?;
+            Ok(ReceivedMessageType::UpdateGameSession {
+                game_session,
+                update_reason: unpack_result.update_reason,
+                backfill_ticket_id: unpack_result.backfill_ticket_id,
+            })
         }
+        Some("type.googleapis.com/com.amazon.whitewater.auxproxy.pbuffer.TerminateProcess") => {
+            let unpack_result: crate::entity::TerminateProcess =
+                unpack_inner_message(inner_message)?;
+            Ok(ReceivedMessageType::TerminateProcess(unpack_result))
+        }
+        Some(message_type) => Err(crate::error::GameLiftErrorType::UnexpectedWebSocketMessage(
+            format!("Unknown message type: {}", message_type),
+        )),
+        None => Err(crate::error::GameLiftErrorType::UnexpectedWebSocketMessage(
+            "The message does not contain @type".to_string(),
+        )),
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::GameLiftErrorType,
+        web_socket_listener::{parse_message, ReceivedMessageType},
+    };
 
-    Err(crate::error::GameLiftErrorType::UnexpectedWebSocketMessage)
+    const TYPE_PREFIX: &str = "type.googleapis.com/com.amazon.whitewater.auxproxy.pbuffer.";
+
+    fn envelope(inner_message: serde_json::Value) -> String {
+        serde_json::json!({ "innerMessage": inner_message }).to_string()
+    }
+
+    fn assert_unexpected(message_text: &str) {
+        assert!(matches!(
+            parse_message(message_text),
+            Err(GameLiftErrorType::UnexpectedWebSocketMessage(_))
+        ));
+    }
+
+    #[test]
+    fn parse_activate_game_session_test() {
+        let message = envelope(serde_json::json!({
+            "@type": format!("{}ActivateGameSession", TYPE_PREFIX),
+            "gameSession": { "gameSessionId": "id", "maxPlayers": 4, "port": 1234 }
+        }));
+
+        assert!(matches!(
+            parse_message(&message),
+            Ok(ReceivedMessageType::ActivateGameSession(message))
+                if message.game_session.game_session_id.as_deref() == Some("id")
+        ));
+    }
+
+    #[test]
+    fn parse_malformed_frame_test() {
+        assert_unexpected("{not a json");
+        assert_unexpected(r#"{"something": "else"}"#);
+    }
+
+    #[test]
+    fn parse_unknown_type_test() {
+        assert_unexpected(&envelope(serde_json::json!({ "@type": "Unknown" })));
+        assert_unexpected(&envelope(serde_json::json!({ "gameSession": {} })));
+    }
+
+    #[test]
+    fn parse_activate_game_session_without_id_test() {
+        assert_unexpected(&envelope(serde_json::json!({
+            "@type": format!("{}ActivateGameSession", TYPE_PREFIX),
+            "gameSession": { "maxPlayers": 4, "port": 1234 }
+        })));
+        assert_unexpected(&envelope(serde_json::json!({
+            "@type": format!("{}ActivateGameSession", TYPE_PREFIX),
+        })));
+    }
+
+    #[test]
+    fn parse_update_game_session_test() {
+        let message = envelope(serde_json::json!({
+            "@type": format!("{}UpdateGameSession", TYPE_PREFIX),
+            "gameSession": { "gameSessionId": "id", "maxPlayers": 4, "port": 1234 },
+            "updateReason": "BACKFILL_TIMED_OUT",
+            "backfillTicketId": "ticket"
+        }));
+
+        assert!(matches!(
+            parse_message(&message),
+            Ok(ReceivedMessageType::UpdateGameSession { update_reason, .. })
+                if update_reason == crate::entity::UpdateReason::BackfillTimedOut
+        ));
+
+        assert_unexpected(&envelope(serde_json::json!({
+            "@type": format!("{}UpdateGameSession", TYPE_PREFIX),
+            "updateReason": "BACKFILL_TIMED_OUT",
+            "backfillTicketId": "ticket"
+        })));
+    }
+
+    #[test]
+    fn parse_terminate_process_without_termination_time_test() {
+        let message = envelope(serde_json::json!({
+            "@type": format!("{}TerminateProcess", TYPE_PREFIX),
+        }));
+
+        assert!(matches!(
+            parse_message(&message),
+            Ok(ReceivedMessageType::TerminateProcess(message))
+                if message.termination_time.is_none()
+        ));
+    }
 }
@@ -0,0 +1,996 @@
+use aws_gamelift_server_sdk_rs::{
+    api::Api,
+    backfill::BackfillTicketState,
+    backfill_policy::{BackfillEvent, BackfillPolicy},
+    capacity_policy::CapacityPolicy,
+    entity::{GameSession, PlayerSessionCreationPolicy},
+    error::GameLiftErrorType,
+    gatekeeper::{line_handshake, GatekeeperConfig},
+    health::HealthCheckResult,
+    idle_policy::{IdlePolicy, IdleReason},
+    log_parameters::LogParameters,
+    notification::Notification,
+    process_parameters::ProcessParameters,
+    protos::generated_with_pure::sdk,
+    sdk_config::{HealthCheckConfig, SdkConfig},
+    sdk_event::SdkEvent,
+    server_state::{GameSessionState, ProcessState},
+    shutdown::{ShutdownConfig, ShutdownCoordinator, ShutdownReason},
+    testing::{MockAuxProxy, MockResponse},
+};
+
+const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[derive(Debug)]
+enum Callback {
+    StartGameSession(GameSession),
+    ProcessTerminate,
+}
+
+fn process_parameters(sender: tokio::sync::mpsc::UnboundedSender<Callback>) -> ProcessParameters {
+    let terminate_sender = sender.clone();
+    ProcessParameters {
+        on_start_game_session: Box::new(move |game_session| {
+            let _ = sender.send(Callback::StartGameSession(game_session));
+            Box::pin(async {})
+        }),
+        on_update_game_session: Box::new(|_| Box::pin(async {})),
+        on_process_terminate: Box::new(move || {
+            let _ = terminate_sender.send(Callback::ProcessTerminate);
+            Box::pin(async {})
+        }),
+        on_health_check: Box::new(|| Box::pin(async { true })),
+        port: 14000,
+        log_parameters: LogParameters { log_paths: vec!["logs".to_string()] },
+    }
+}
+
+async fn next_callback(receiver: &mut tokio::sync::mpsc::UnboundedReceiver<Callback>) -> Callback {
+    tokio::time::timeout(TIMEOUT, receiver.recv())
+        .await
+        .expect("Timed out waiting for a callback")
+        .expect("Callback channel closed")
+}
+
+/// Starts the mock AuxProxy and an SDK connected to it.
+async fn connected_api(configure: impl FnOnce(SdkConfig) -> SdkConfig) -> (MockAuxProxy, Api) {
+    let aux_proxy = MockAuxProxy::start_on(0, 0).await.expect("Cannot start the mock AuxProxy");
+    let api = Api::new(configure(aux_proxy.sdk_config()));
+    api.init_sdk().await.expect("Cannot initialize the SDK");
+    assert!(aux_proxy.wait_for_connections(1, TIMEOUT).await);
+    (aux_proxy, api)
+}
+
+/// Registers the process and activates `game_session` as the current game
+/// session.
+async fn active_game_session(
+    game_session: sdk::GameSession,
+) -> (MockAuxProxy, Api, tokio::sync::mpsc::UnboundedReceiver<Callback>) {
+    let (aux_proxy, api) = connected_api(|config| config).await;
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    api.process_ready(process_parameters(sender)).await.expect("ProcessReady failed");
+    aux_proxy.activate_game_session(game_session);
+    assert!(matches!(next_callback(&mut receiver).await, Callback::StartGameSession(_)));
+    api.activate_game_session().await.expect("Cannot activate the game session");
+    (aux_proxy, api, receiver)
+}
+
+#[tokio::test]
+async fn end_to_end_test() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let (aux_proxy, api) = connected_api(|config| config).await;
+
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    api.process_ready(process_parameters(sender)).await.expect("ProcessReady failed");
+    let process_ready: sdk::ProcessReady =
+        aux_proxy.wait_for_message("ProcessReady", TIMEOUT).await.unwrap().decode().unwrap();
+    assert_eq!(process_ready.port, 14000);
+    assert_eq!(process_ready.logPathsToUpload.to_vec(), vec!["logs".to_string()]);
+
+    aux_proxy.activate_game_session(sdk::GameSession {
+        gameSessionId: "gsess-1".to_string(),
+        maxPlayers: 4,
+        ..Default::default()
+    });
+    match next_callback(&mut receiver).await {
+        Callback::StartGameSession(game_session) => {
+            assert_eq!(game_session.game_session_id.as_deref(), Some("gsess-1"));
+            assert_eq!(game_session.max_players, 4);
+        }
+        callback => panic!("Unexpected callback: {:?}", callback),
+    }
+
+    api.activate_game_session().await.expect("Cannot activate the game session");
+    let activate: sdk::GameSessionActivate =
+        aux_proxy.wait_for_message("GameSessionActivate", TIMEOUT).await.unwrap().decode().unwrap();
+    assert_eq!(activate.gameSessionId, "gsess-1");
+    assert_eq!(api.state(), ProcessState::SessionActive);
+    assert!(matches!(
+        api.activate_game_session().await,
+        Err(GameLiftErrorType::IllegalState { state: ProcessState::SessionActive, .. })
+    ));
+
+    aux_proxy.set_response(
+        "AcceptPlayerSession",
+        MockResponse::with_status(
+            400,
+            r#"{"status":"ERROR_400","errorMessage":"Invalid player session"}"#,
+        ),
+    );
+    assert!(matches!(
+        api.accept_player_session("psess-1".to_string()).await,
+        Err(GameLiftErrorType::BadRequest(message)) if message == "Invalid player session"
+    ));
+
+    let mut notifications = api.subscribe_notifications();
+    aux_proxy.disconnect_clients();
+    assert_eq!(
+        tokio::time::timeout(TIMEOUT, notifications.recv()).await.unwrap().unwrap(),
+        Notification::Disconnected
+    );
+    assert!(matches!(
+        tokio::time::timeout(TIMEOUT, notifications.recv()).await.unwrap().unwrap(),
+        Notification::Reconnected { .. }
+    ));
+    assert!(aux_proxy.wait_for_messages("ProcessReady", 2, TIMEOUT).await.is_some());
+    assert!(aux_proxy.wait_for_messages("GameSessionActivate", 2, TIMEOUT).await.is_some());
+
+    aux_proxy.terminate_process(1234);
+    assert!(matches!(next_callback(&mut receiver).await, Callback::ProcessTerminate));
+    assert_eq!(api.get_termination_time().await.unwrap(), 1234);
+    assert_eq!(
+        api.termination_countdown().await.unwrap().deadline(),
+        std::time::UNIX_EPOCH + std::time::Duration::from_secs(1234)
+    );
+
+    // No more reconnection attempts once the process is ending.
+    api.process_ending().await.expect("ProcessEnding failed");
+    let mut notifications = api.subscribe_notifications();
+    aux_proxy.disconnect_clients();
+    assert_eq!(
+        tokio::time::timeout(TIMEOUT, notifications.recv()).await.unwrap().unwrap(),
+        Notification::ConnectionClosed
+    );
+    assert_eq!(aux_proxy.connection_count(), 2);
+}
+
+#[tokio::test]
+async fn stalled_handshake_test() {
+    let aux_proxy = MockAuxProxy::start_on(0, 0).await.expect("Cannot start the mock AuxProxy");
+    // Connects but never sends the WebSocket handshake.
+    let _stalled =
+        tokio::net::TcpStream::connect(("127.0.0.1", aux_proxy.web_socket_port())).await.unwrap();
+
+    let api = Api::new(aux_proxy.sdk_config());
+    tokio::time::timeout(TIMEOUT, api.init_sdk())
+        .await
+        .expect("The stalled client blocks the others")
+        .expect("Cannot initialize the SDK");
+    assert!(aux_proxy.wait_for_connections(1, TIMEOUT).await);
+}
+
+#[tokio::test]
+async fn event_stream_test() {
+    let (aux_proxy, api) = connected_api(|config| config).await;
+
+    let mut events = api
+        .process_ready_with_events(14000, LogParameters { log_paths: vec![] })
+        .await
+        .expect("ProcessReady failed");
+    aux_proxy.activate_game_session(sdk::GameSession {
+        gameSessionId: "gsess-1".to_string(),
+        ..Default::default()
+    });
+
+    let mut game_session = None;
+    while game_session.is_none() {
+        match tokio::time::timeout(TIMEOUT, events.recv()).await.unwrap().unwrap() {
+            SdkEvent::HealthCheck(responder) => responder.respond(true),
+            SdkEvent::StartGameSession(started) => game_session = Some(started),
+            event => panic!("Unexpected event: {:?}", event),
+        }
+    }
+    assert_eq!(game_session.unwrap().game_session_id.as_deref(), Some("gsess-1"));
+    let report_health: sdk::ReportHealth =
+        aux_proxy.wait_for_message("ReportHealth", TIMEOUT).await.unwrap().decode().unwrap();
+    assert!(report_health.healthStatus);
+
+    assert!(api.destroy().await);
+    assert!(tokio::time::timeout(TIMEOUT, events.recv()).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn multiple_game_sessions_test() {
+    let (aux_proxy, api) =
+        connected_api(|config| SdkConfig { max_concurrent_game_sessions: 2, ..config }).await;
+
+    let mut events = api
+        .process_ready_with_events(14000, LogParameters { log_paths: vec![] })
+        .await
+        .expect("ProcessReady failed");
+    let process_ready: sdk::ProcessReady =
+        aux_proxy.wait_for_message("ProcessReady", TIMEOUT).await.unwrap().decode().unwrap();
+    assert_eq!(process_ready.maxConcurrentGameSessions, 2);
+
+    for game_session_id in ["gsess-1", "gsess-2", "gsess-3"] {
+        aux_proxy.activate_game_session(sdk::GameSession {
+            gameSessionId: game_session_id.to_string(),
+            ..Default::default()
+        });
+    }
+    let mut started = vec![];
+    while started.len() < 2 {
+        match tokio::time::timeout(TIMEOUT, events.recv()).await.unwrap().unwrap() {
+            SdkEvent::HealthCheck(responder) => responder.respond(true),
+            SdkEvent::StartGameSession(game_session) => {
+                started.push(game_session.game_session_id.unwrap())
+            }
+            event => panic!("Unexpected event: {:?}", event),
+        }
+    }
+    assert_eq!(started, vec!["gsess-1".to_string(), "gsess-2".to_string()]);
+    // The third game session does not fit. Events are handled in order, so it
+    // has been dropped by the time the next event arrives.
+    aux_proxy.terminate_process(1234);
+    loop {
+        match tokio::time::timeout(TIMEOUT, events.recv()).await.unwrap().unwrap() {
+            SdkEvent::HealthCheck(responder) => responder.respond(true),
+            SdkEvent::ProcessTerminate { .. } => break,
+            event => panic!("Unexpected event: {:?}", event),
+        }
+    }
+    assert_eq!(api.game_sessions().len(), 2);
+    assert!(api.game_session("gsess-3").is_none());
+    assert!(matches!(
+        api.activate_game_session().await,
+        Err(GameLiftErrorType::AmbiguousGameSession)
+    ));
+
+    let first = api.game_session("gsess-1").unwrap();
+    first.activate().await.expect("Cannot activate the game session");
+    assert_eq!(first.state(), Some(GameSessionState::Active));
+    assert_eq!(api.state(), ProcessState::SessionActive);
+    first.accept_player_session("psess-1".to_string()).await.unwrap();
+    let accept: sdk::AcceptPlayerSession =
+        aux_proxy.wait_for_message("AcceptPlayerSession", TIMEOUT).await.unwrap().decode().unwrap();
+    assert_eq!(accept.gameSessionId, "gsess-1");
+    assert_eq!(first.player_count(), 1);
+    assert!(matches!(
+        first.accept_player_session("psess-1".to_string()).await,
+        Err(GameLiftErrorType::PlayerSessionAlreadyAccepted(_))
+    ));
+    assert_eq!(aux_proxy.received_messages_of_type("AcceptPlayerSession").len(), 1);
+
+    let mut notifications = api.subscribe_notifications();
+    first.terminate().await.expect("Cannot terminate the game session");
+    let notification = std::iter::from_fn(|| notifications.try_recv().ok())
+        .find(|notification| !matches!(notification, Notification::StateChanged { .. }));
+    assert_eq!(
+        notification.unwrap(),
+        Notification::PlayerSessionsLeaked {
+            game_session_id: "gsess-1".to_string(),
+            player_session_ids: vec!["psess-1".to_string()],
+        }
+    );
+    assert_eq!(first.state(), None);
+    let terminate: sdk::GameSessionTerminate = aux_proxy
+        .wait_for_message("GameSessionTerminate", TIMEOUT)
+        .await
+        .unwrap()
+        .decode()
+        .unwrap();
+    assert_eq!(terminate.gameSessionId, "gsess-1");
+    assert_eq!(api.state(), ProcessState::SessionStarting);
+
+    // A single game session is the current one again.
+    api.activate_game_session().await.expect("Cannot activate the game session");
+    let activate: sdk::GameSessionActivate = aux_proxy
+        .wait_for_messages("GameSessionActivate", 2, TIMEOUT)
+        .await
+        .unwrap()
+        .decode()
+        .unwrap();
+    assert_eq!(activate.gameSessionId, "gsess-2");
+}
+
+fn player_sessions_page(player_session_ids: &[&str], next_token: &str) -> MockResponse {
+    let mut page = sdk::DescribePlayerSessionsResponse {
+        nextToken: next_token.to_string(),
+        ..Default::default()
+    };
+    for player_session_id in player_session_ids {
+        page.playerSessions.push(sdk::PlayerSession {
+            playerSessionId: player_session_id.to_string(),
+            gameSessionId: "gsess-1".to_string(),
+            status: "ACTIVE".to_string(),
+            ..Default::default()
+        });
+    }
+    MockResponse::ok(serde_json::to_string(&page).unwrap())
+}
+
+#[tokio::test]
+async fn describe_player_sessions_stream_test() {
+    use futures_util::StreamExt;
+
+    let (aux_proxy, api) = connected_api(|config| config).await;
+
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    api.process_ready(process_parameters(sender)).await.expect("ProcessReady failed");
+    aux_proxy.activate_game_session(sdk::GameSession {
+        gameSessionId: "gsess-1".to_string(),
+        ..Default::default()
+    });
+    assert!(matches!(next_callback(&mut receiver).await, Callback::StartGameSession(_)));
+
+    aux_proxy.push_response(
+        "DescribePlayerSessionsRequest",
+        player_sessions_page(&["psess-1", "psess-2"], "page-2"),
+    );
+    aux_proxy
+        .push_response("DescribePlayerSessionsRequest", player_sessions_page(&["psess-3"], ""));
+    let player_sessions: Vec<_> =
+        api.active_player_sessions().await.expect("No current game session").collect().await;
+    let player_session_ids: Vec<_> = player_sessions
+        .into_iter()
+        .map(|player_session| player_session.unwrap().player_session_id.unwrap())
+        .collect();
+    assert_eq!(player_session_ids, vec!["psess-1", "psess-2", "psess-3"]);
+
+    let requests: Vec<sdk::DescribePlayerSessionsRequest> = aux_proxy
+        .received_messages_of_type("DescribePlayerSessionsRequest")
+        .iter()
+        .map(|message| message.decode().unwrap())
+        .collect();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].gameSessionId, "gsess-1");
+    assert_eq!(requests[0].playerSessionStatusFilter, "ACTIVE");
+    assert_eq!(requests[0].limit, 50);
+    assert_eq!(requests[0].nextToken, "");
+    assert_eq!(requests[1].nextToken, "page-2");
+
+    aux_proxy.push_response(
+        "DescribePlayerSessionsRequest",
+        player_sessions_page(&["psess-1"], "page-2"),
+    );
+    aux_proxy.push_response(
+        "DescribePlayerSessionsRequest",
+        MockResponse::with_status(500, r#"{"status":"ERROR_500","errorMessage":"Unavailable"}"#),
+    );
+    let handle = api.game_session("gsess-1").unwrap();
+    let results: Vec<_> = handle.describe_player_sessions(None).collect().await;
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(GameLiftErrorType::InternalServiceError(_))));
+}
+
+fn matchmaker_data(player_ids: &[&str]) -> String {
+    let players: Vec<_> =
+        player_ids.iter().map(|player_id| serde_json::json!({ "playerId": player_id })).collect();
+    serde_json::json!({
+        "matchId": "match-1",
+        "matchmakingConfigurationArn": "arn:aws:gamelift:us-west-2:111122223333:matchmakingconfiguration/Config",
+        "teams": [{ "name": "red", "players": players }],
+    })
+    .to_string()
+}
+
+/// A describe_player_sessions response with a player session of a player who
+/// has left.
+fn completed_player_session(player_session_id: &str, player_id: &str) -> MockResponse {
+    let mut page = sdk::DescribePlayerSessionsResponse::default();
+    page.playerSessions.push(sdk::PlayerSession {
+        playerSessionId: player_session_id.to_string(),
+        playerId: player_id.to_string(),
+        gameSessionId: "gsess-1".to_string(),
+        status: "COMPLETED".to_string(),
+        ..Default::default()
+    });
+    MockResponse::ok(serde_json::to_string(&page).unwrap())
+}
+
+#[tokio::test]
+async fn backfill_manager_test() {
+    let game_session = sdk::GameSession {
+        gameSessionId: "gsess-1".to_string(),
+        matchmakerData: matchmaker_data(&["player-1", "player-2"]),
+        ..Default::default()
+    };
+    let (aux_proxy, api, _receiver) = active_game_session(game_session.clone()).await;
+
+    aux_proxy.set_response("BackfillMatchmakingRequest", MockResponse::ok(r#"{"ticketId":""}"#));
+    let backfill = api.backfill().await.expect("No current game session");
+    let ticket = backfill.start().await.expect("Cannot start backfill");
+    assert_eq!(ticket.state, BackfillTicketState::Pending);
+    assert!(matches!(
+        backfill.start().await,
+        Err(GameLiftErrorType::BackfillAlreadyActive(ticket_id)) if ticket_id == ticket.ticket_id
+    ));
+    let request: sdk::BackfillMatchmakingRequest = aux_proxy
+        .wait_for_message("BackfillMatchmakingRequest", TIMEOUT)
+        .await
+        .unwrap()
+        .decode()
+        .unwrap();
+    assert_eq!(request.ticketId, ticket.ticket_id);
+    assert_eq!(request.gameSessionArn, "gsess-1");
+    assert!(request.matchmakingConfigurationArn.ends_with("/Config"));
+    assert_eq!(request.players.len(), 2);
+    assert_eq!(request.players[0].team, "red");
+
+    let mut notifications = api.subscribe_notifications();
+    aux_proxy.update_game_session(
+        sdk::GameSession {
+            matchmakerData: matchmaker_data(&["player-1", "player-2", "player-3"]),
+            ..game_session
+        },
+        "MATCHMAKING_DATA_UPDATED",
+        &ticket.ticket_id,
+    );
+    loop {
+        match tokio::time::timeout(TIMEOUT, notifications.recv()).await.unwrap().unwrap() {
+            Notification::BackfillTicketUpdated { ticket_id, state, .. } => {
+                assert_eq!(ticket_id, ticket.ticket_id);
+                assert_eq!(state, BackfillTicketState::Fulfilled);
+                break;
+            }
+            _ => continue,
+        }
+    }
+    assert!(backfill.active_ticket().is_none());
+    let matchmaker_data =
+        api.game_session("gsess-1").unwrap().game_session().unwrap().matchmaker_data().unwrap();
+    assert_eq!(matchmaker_data.unwrap().players().len(), 3);
+
+    let ticket = backfill.start().await.expect("Cannot start backfill");
+    backfill.stop().await.expect("Cannot stop backfill");
+    let stop: sdk::StopMatchmakingRequest = aux_proxy
+        .wait_for_message("StopMatchmakingRequest", TIMEOUT)
+        .await
+        .unwrap()
+        .decode()
+        .unwrap();
+    assert_eq!(stop.ticketId, ticket.ticket_id);
+    assert_eq!(backfill.ticket(&ticket.ticket_id).unwrap().state, BackfillTicketState::Cancelled);
+    assert!(matches!(backfill.stop().await, Err(GameLiftErrorType::BackfillNotActive)));
+    assert_eq!(backfill.tickets().len(), 2);
+
+    // Only describing the player session tells which player has left.
+    api.accept_player_session("psess-2".to_string()).await.unwrap();
+    api.remove_player_session("psess-2".to_string()).await.unwrap();
+    aux_proxy.set_response(
+        "DescribePlayerSessionsRequest",
+        completed_player_session("psess-2", "player-2"),
+    );
+    backfill.start().await.expect("Cannot start backfill");
+    let describe: sdk::DescribePlayerSessionsRequest = aux_proxy
+        .wait_for_message("DescribePlayerSessionsRequest", TIMEOUT)
+        .await
+        .unwrap()
+        .decode()
+        .unwrap();
+    assert_eq!(describe.gameSessionId, "gsess-1");
+    let request: sdk::BackfillMatchmakingRequest = aux_proxy
+        .wait_for_messages("BackfillMatchmakingRequest", 3, TIMEOUT)
+        .await
+        .unwrap()
+        .decode()
+        .unwrap();
+    let player_ids: Vec<_> =
+        request.players.iter().map(|player| player.playerId.as_str()).collect();
+    assert_eq!(player_ids, vec!["player-1", "player-3"]);
+    assert_eq!(
+        api.game_session("gsess-1")
+            .unwrap()
+            .player_session("psess-2")
+            .unwrap()
+            .player_id
+            .as_deref(),
+        Some("player-2")
+    );
+}
+
+async fn next_backfill_event(
+    receiver: &mut tokio::sync::mpsc::UnboundedReceiver<BackfillEvent>,
+) -> BackfillEvent {
+    tokio::time::timeout(TIMEOUT, receiver.recv())
+        .await
+        .expect("Timed out waiting for a backfill event")
+        .expect("Backfill event channel closed")
+}
+
+#[tokio::test]
+async fn backfill_policy_test() {
+    let game_session = sdk::GameSession {
+        gameSessionId: "gsess-1".to_string(),
+        maxPlayers: 2,
+        matchmakerData: matchmaker_data(&["player-1", "player-2"]),
+        ..Default::default()
+    };
+    let (aux_proxy, api, _receiver) = active_game_session(game_session.clone()).await;
+    api.accept_player_session("psess-1".to_string()).await.unwrap();
+    api.accept_player_session("psess-2".to_string()).await.unwrap();
+    aux_proxy.set_response("BackfillMatchmakingRequest", MockResponse::ok(r#"{"ticketId":""}"#));
+    aux_proxy.set_response(
+        "DescribePlayerSessionsRequest",
+        completed_player_session("psess-2", "player-2"),
+    );
+
+    let (event_sender, mut events) = tokio::sync::mpsc::unbounded_channel();
+    let policy = BackfillPolicy {
+        retry_delay: std::time::Duration::from_millis(10),
+        on_event: Some(Box::new(move |event| {
+            let _ = event_sender.send(event);
+            Box::pin(async {})
+        })),
+        ..BackfillPolicy::new(2)
+    };
+    let _policy = api.backfill().await.unwrap().run_policy(policy);
+
+    api.remove_player_session("psess-2".to_string()).await.unwrap();
+    let ticket = match next_backfill_event(&mut events).await {
+        BackfillEvent::Started(ticket) => ticket,
+        event => panic!("Unexpected event: {:?}", event),
+    };
+    // The player who left is not sent as a current player, so that FlexMatch
+    // fills the slot.
+    let request: sdk::BackfillMatchmakingRequest = aux_proxy
+        .wait_for_message("BackfillMatchmakingRequest", TIMEOUT)
+        .await
+        .unwrap()
+        .decode()
+        .unwrap();
+    let player_ids: Vec<_> =
+        request.players.iter().map(|player| player.playerId.as_str()).collect();
+    assert_eq!(player_ids, vec!["player-1"]);
+
+    aux_proxy.update_game_session(game_session.clone(), "BACKFILL_TIMED_OUT", &ticket.ticket_id);
+    assert!(matches!(
+        next_backfill_event(&mut events).await,
+        BackfillEvent::RetryScheduled { attempt: 1, .. }
+    ));
+    assert!(matches!(
+        next_backfill_event(&mut events).await,
+        BackfillEvent::Finished(finished) if finished.state == BackfillTicketState::TimedOut
+    ));
+    let ticket = match next_backfill_event(&mut events).await {
+        BackfillEvent::Started(retried) => retried,
+        event => panic!("Unexpected event: {:?}", event),
+    };
+
+    api.accept_player_session("psess-3".to_string()).await.unwrap();
+    assert!(matches!(
+        next_backfill_event(&mut events).await,
+        BackfillEvent::Finished(finished)
+            if finished.ticket_id == ticket.ticket_id
+                && finished.state == BackfillTicketState::Cancelled
+    ));
+    assert_eq!(aux_proxy.received_messages_of_type("BackfillMatchmakingRequest").len(), 2);
+    assert_eq!(aux_proxy.received_messages_of_type("StopMatchmakingRequest").len(), 1);
+}
+
+#[tokio::test]
+async fn backfill_policy_not_retryable_test() {
+    let (aux_proxy, api, _receiver) = active_game_session(sdk::GameSession {
+        gameSessionId: "gsess-1".to_string(),
+        ..Default::default()
+    })
+    .await;
+
+    let (event_sender, mut events) = tokio::sync::mpsc::unbounded_channel();
+    let policy = BackfillPolicy {
+        retry_delay: std::time::Duration::from_millis(10),
+        on_event: Some(Box::new(move |event| {
+            let _ = event_sender.send(event);
+            Box::pin(async {})
+        })),
+        ..BackfillPolicy::new(2)
+    };
+    let policy = api.backfill().await.unwrap().run_policy(policy);
+
+    // Without matchmaker data a retry cannot succeed, so the policy stops.
+    assert!(matches!(
+        next_backfill_event(&mut events).await,
+        BackfillEvent::StartFailed(GameLiftErrorType::MatchmakerDataNotSet)
+    ));
+    assert!(tokio::time::timeout(TIMEOUT, events.recv()).await.unwrap().is_none());
+    assert!(policy.is_finished());
+    assert!(aux_proxy.received_messages_of_type("BackfillMatchmakingRequest").is_empty());
+}
+
+#[tokio::test]
+async fn backfill_policy_fulfilled_test() {
+    let game_session = sdk::GameSession {
+        gameSessionId: "gsess-1".to_string(),
+        maxPlayers: 4,
+        matchmakerData: matchmaker_data(&["player-1"]),
+        ..Default::default()
+    };
+    let (aux_proxy, api, _receiver) = active_game_session(game_session.clone()).await;
+    api.accept_player_session("psess-1".to_string()).await.unwrap();
+    aux_proxy.set_response("BackfillMatchmakingRequest", MockResponse::ok(r#"{"ticketId":""}"#));
+
+    let (event_sender, mut events) = tokio::sync::mpsc::unbounded_channel();
+    let settle_delay = std::time::Duration::from_millis(500);
+    let policy = BackfillPolicy {
+        settle_delay,
+        on_event: Some(Box::new(move |event| {
+            let _ = event_sender.send(event);
+            Box::pin(async {})
+        })),
+        ..BackfillPolicy::new(2)
+    };
+    let _policy = api.backfill().await.unwrap().run_policy(policy);
+    let ticket = match next_backfill_event(&mut events).await {
+        BackfillEvent::Started(ticket) => ticket,
+        event => panic!("Unexpected event: {:?}", event),
+    };
+
+    // The matched player holds a reservation but has not connected yet.
+    let fulfilled_at = tokio::time::Instant::now();
+    aux_proxy.update_game_session(
+        sdk::GameSession {
+            matchmakerData: matchmaker_data(&["player-1", "player-2"]),
+            ..game_session
+        },
+        "MATCHMAKING_DATA_UPDATED",
+        &ticket.ticket_id,
+    );
+    assert!(matches!(
+        next_backfill_event(&mut events).await,
+        BackfillEvent::Finished(finished) if finished.state == BackfillTicketState::Fulfilled
+    ));
+    assert_eq!(api.player_count(), 1);
+
+    // The player never shows up, so backfill starts again after the delay.
+    assert!(matches!(next_backfill_event(&mut events).await, BackfillEvent::Started(_)));
+    assert!(fulfilled_at.elapsed() >= settle_delay);
+    assert_eq!(aux_proxy.received_messages_of_type("BackfillMatchmakingRequest").len(), 2);
+}
+
+#[tokio::test]
+async fn shutdown_coordinator_test() {
+    let (aux_proxy, api, _receiver) = active_game_session(sdk::GameSession {
+        gameSessionId: "gsess-1".to_string(),
+        ..Default::default()
+    })
+    .await;
+    api.accept_player_session("psess-1".to_string()).await.unwrap();
+
+    let (context_sender, mut contexts) = tokio::sync::mpsc::unbounded_channel();
+    let coordinator = ShutdownCoordinator::new(
+        api.clone(),
+        ShutdownConfig { handle_signals: false, ..Default::default() },
+    )
+    .on_shutdown(Box::new(move |context| {
+        let _ = context_sender.send(context);
+        Box::pin(async {})
+    }));
+    let shutdown = tokio::spawn(async move { coordinator.run().await });
+
+    let termination_time =
+        std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs()
+            as i64
+            + 300;
+    aux_proxy.terminate_process(termination_time);
+    let context = tokio::time::timeout(TIMEOUT, contexts.recv()).await.unwrap().unwrap();
+    assert_eq!(context.reason, ShutdownReason::TerminateProcess);
+    assert_eq!(
+        context.deadline,
+        std::time::UNIX_EPOCH + std::time::Duration::from_secs(termination_time as u64)
+    );
+    let policy: sdk::UpdatePlayerSessionCreationPolicy = aux_proxy
+        .wait_for_message("UpdatePlayerSessionCreationPolicy", TIMEOUT)
+        .await
+        .unwrap()
+        .decode()
+        .unwrap();
+    assert_eq!(policy.newPlayerSessionCreationPolicy, "DenyAll");
+
+    // The coordinator waits for the last player to leave.
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert!(aux_proxy.received_messages_of_type("ProcessEnding").is_empty());
+    api.remove_player_session("psess-1".to_string()).await.unwrap();
+
+    let exit_code = tokio::time::timeout(TIMEOUT, shutdown).await.unwrap().unwrap();
+    assert_eq!(exit_code, 0);
+    assert_eq!(aux_proxy.received_messages_of_type("ProcessEnding").len(), 1);
+    assert_eq!(api.state(), ProcessState::Ended);
+}
+
+#[tokio::test]
+async fn health_registry_test() {
+    let (aux_proxy, api) = connected_api(|config| SdkConfig {
+        health_check: HealthCheckConfig {
+            interval: std::time::Duration::from_millis(20),
+            timeout: std::time::Duration::from_secs(1),
+            jitter: std::time::Duration::from_millis(5),
+        },
+        ..config
+    })
+    .await;
+    api.health_registry().register(
+        "database",
+        Box::new(|| Box::pin(async { HealthCheckResult::unhealthy("no connection") })),
+    );
+
+    let (sender, _receiver) = tokio::sync::mpsc::unbounded_channel();
+    api.process_ready(process_parameters(sender)).await.expect("ProcessReady failed");
+    assert!(aux_proxy.wait_for_messages("ReportHealth", 2, TIMEOUT).await.is_some());
+    for report in aux_proxy.received_messages_of_type("ReportHealth") {
+        let report: sdk::ReportHealth = report.decode().unwrap();
+        assert!(!report.healthStatus);
+    }
+
+    let snapshot = api.health_snapshot().expect("No health report yet");
+    assert!(!snapshot.healthy);
+    let unhealthy: Vec<_> =
+        snapshot.unhealthy_checks().map(|status| status.name.as_str()).collect();
+    assert_eq!(unhealthy, vec!["database"]);
+    assert!(api.destroy().await);
+}
+
+#[tokio::test]
+async fn gatekeeper_test() {
+    use tokio::io::AsyncWriteExt;
+
+    let (aux_proxy, api, _receiver) = active_game_session(sdk::GameSession {
+        gameSessionId: "gsess-1".to_string(),
+        ..Default::default()
+    })
+    .await;
+
+    let gatekeeper = api.gatekeeper(
+        GatekeeperConfig { handshake_timeout: std::time::Duration::from_millis(100) },
+        line_handshake(),
+    );
+    let mut notifications = api.subscribe_notifications();
+    let (mut client, server) = tokio::io::duplex(64);
+    client.write_all(b"psess-1\n").await.unwrap();
+    let connection = gatekeeper.admit(server).await.expect("Cannot admit the player");
+    assert_eq!(connection.player_session_id(), "psess-1");
+    let accept: sdk::AcceptPlayerSession =
+        aux_proxy.wait_for_message("AcceptPlayerSession", TIMEOUT).await.unwrap().decode().unwrap();
+    assert_eq!(accept.playerSessionId, "psess-1");
+    assert_eq!(api.player_count(), 1);
+
+    drop(connection);
+    let remove: sdk::RemovePlayerSession =
+        aux_proxy.wait_for_message("RemovePlayerSession", TIMEOUT).await.unwrap().decode().unwrap();
+    assert_eq!(remove.playerSessionId, "psess-1");
+    while !matches!(
+        tokio::time::timeout(TIMEOUT, notifications.recv()).await.unwrap().unwrap(),
+        Notification::PlayerCountChanged { player_count: 0, .. }
+    ) {}
+
+    let (mut client, server) = tokio::io::duplex(64);
+    client.write_all(b"psess-2\n").await.unwrap();
+    let connection = gatekeeper.admit(server).await.expect("Cannot admit the player");
+    connection.close().await.expect("Cannot remove the player session");
+    assert!(aux_proxy.wait_for_messages("RemovePlayerSession", 2, TIMEOUT).await.is_some());
+    assert_eq!(api.player_count(), 0);
+
+    aux_proxy.push_response(
+        "AcceptPlayerSession",
+        MockResponse::with_status(
+            400,
+            r#"{"status":"ERROR_400","errorMessage":"Invalid player session"}"#,
+        ),
+    );
+    let (mut client, server) = tokio::io::duplex(64);
+    client.write_all(b"psess-3\n").await.unwrap();
+    assert!(matches!(
+        gatekeeper.admit(server).await,
+        Err(GameLiftErrorType::BadRequest(message)) if message == "Invalid player session"
+    ));
+
+    let (_client, server) = tokio::io::duplex(64);
+    assert!(matches!(gatekeeper.admit(server).await, Err(GameLiftErrorType::HandshakeTimedOut)));
+    assert_eq!(aux_proxy.received_messages_of_type("AcceptPlayerSession").len(), 3);
+}
+
+#[tokio::test]
+async fn idle_policy_test() {
+    let (aux_proxy, api, _receiver) = active_game_session(sdk::GameSession {
+        gameSessionId: "gsess-1".to_string(),
+        ..Default::default()
+    })
+    .await;
+
+    let (hook_sender, mut hooks) = tokio::sync::mpsc::unbounded_channel();
+    let veto_sender = hook_sender.clone();
+    let vetoes = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let timeout = std::time::Duration::from_millis(300);
+    let policy = api
+        .run_idle_policy(IdlePolicy {
+            no_player_timeout: Some(timeout),
+            empty_timeout: Some(timeout),
+            veto: Some(Box::new(move |context| {
+                let _ = veto_sender.send(("veto", context));
+                // Keep the game session only the first time.
+                let veto = vetoes.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0;
+                Box::pin(async move { veto })
+            })),
+            before_ending: Some(Box::new(move |context| {
+                let _ = hook_sender.send(("before_ending", context));
+                Box::pin(async {})
+            })),
+        })
+        .await
+        .expect("Cannot run the idle policy");
+
+    let (hook, context) = tokio::time::timeout(TIMEOUT, hooks.recv()).await.unwrap().unwrap();
+    assert_eq!(hook, "veto");
+    assert_eq!(context.reason, IdleReason::NoPlayerJoined);
+    assert!(context.idle_for >= timeout);
+    api.accept_player_session("psess-1".to_string()).await.unwrap();
+    api.remove_player_session("psess-1".to_string()).await.unwrap();
+
+    let (hook, context) = tokio::time::timeout(TIMEOUT, hooks.recv()).await.unwrap().unwrap();
+    assert_eq!((hook, context.reason), ("veto", IdleReason::Empty));
+    let (hook, context) = tokio::time::timeout(TIMEOUT, hooks.recv()).await.unwrap().unwrap();
+    assert_eq!((hook, context.reason), ("before_ending", IdleReason::Empty));
+    assert!(aux_proxy.wait_for_message("ProcessEnding", TIMEOUT).await.is_some());
+    tokio::time::timeout(TIMEOUT, async {
+        while !policy.is_finished() {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(api.state(), ProcessState::Ended);
+}
+
+#[tokio::test]
+async fn idle_policy_reserved_test() {
+    let (aux_proxy, api, _receiver) = active_game_session(sdk::GameSession {
+        gameSessionId: "gsess-1".to_string(),
+        ..Default::default()
+    })
+    .await;
+
+    let (hook_sender, mut hooks) = tokio::sync::mpsc::unbounded_channel();
+    let timeout = std::time::Duration::from_millis(300);
+    let _policy = api
+        .run_idle_policy(IdlePolicy {
+            no_player_timeout: Some(timeout),
+            empty_timeout: Some(TIMEOUT * 2),
+            veto: None,
+            before_ending: Some(Box::new(move |context| {
+                let _ = hook_sender.send(context);
+                Box::pin(async {})
+            })),
+        })
+        .await
+        .expect("Cannot run the idle policy");
+
+    // A reservation of a player who has not connected yet.
+    let mut page = sdk::DescribePlayerSessionsResponse::default();
+    page.playerSessions.push(sdk::PlayerSession {
+        playerSessionId: "psess-1".to_string(),
+        playerId: "player-1".to_string(),
+        gameSessionId: "gsess-1".to_string(),
+        status: "RESERVED".to_string(),
+        ..Default::default()
+    });
+    aux_proxy.set_response(
+        "DescribePlayerSessionsRequest",
+        MockResponse::ok(serde_json::to_string(&page).unwrap()),
+    );
+    api.describe_player_sessions(Default::default()).await.unwrap();
+    assert_eq!(api.game_session("gsess-1").unwrap().player_sessions().len(), 1);
+
+    let context = tokio::time::timeout(TIMEOUT, hooks.recv()).await.unwrap().unwrap();
+    assert_eq!(context.reason, IdleReason::NoPlayerJoined);
+    assert!(aux_proxy.wait_for_message("ProcessEnding", TIMEOUT).await.is_some());
+}
+
+async fn creation_policy_update(aux_proxy: &MockAuxProxy, count: usize) -> String {
+    let policy: sdk::UpdatePlayerSessionCreationPolicy = aux_proxy
+        .wait_for_messages("UpdatePlayerSessionCreationPolicy", count, TIMEOUT)
+        .await
+        .unwrap()
+        .decode()
+        .unwrap();
+    policy.newPlayerSessionCreationPolicy
+}
+
+#[tokio::test]
+async fn capacity_policy_test() {
+    let (aux_proxy, api, _receiver) = active_game_session(sdk::GameSession {
+        gameSessionId: "gsess-1".to_string(),
+        maxPlayers: 2,
+        ..Default::default()
+    })
+    .await;
+
+    let policy = api
+        .run_capacity_policy(CapacityPolicy::default())
+        .await
+        .expect("Cannot run the capacity policy");
+    assert_eq!(creation_policy_update(&aux_proxy, 1).await, "AcceptAll");
+
+    api.accept_player_session("psess-1".to_string()).await.unwrap();
+    api.accept_player_session("psess-2".to_string()).await.unwrap();
+    assert_eq!(creation_policy_update(&aux_proxy, 2).await, "DenyAll");
+    // One below the cap is within the reopen margin.
+    api.remove_player_session("psess-2".to_string()).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert_eq!(policy.policy(), Some(PlayerSessionCreationPolicy::DenyAll));
+    api.remove_player_session("psess-1".to_string()).await.unwrap();
+    assert_eq!(creation_policy_update(&aux_proxy, 3).await, "AcceptAll");
+
+    policy.set_draining(true);
+    assert_eq!(creation_policy_update(&aux_proxy, 4).await, "DenyAll");
+    policy.set_draining(false);
+    assert_eq!(creation_policy_update(&aux_proxy, 5).await, "AcceptAll");
+    assert!(!policy.is_draining());
+    assert_eq!(aux_proxy.received_messages_of_type("UpdatePlayerSessionCreationPolicy").len(), 5);
+}
+
+#[tokio::test]
+async fn capacity_policy_shutdown_test() {
+    let (aux_proxy, api, _receiver) = active_game_session(sdk::GameSession {
+        gameSessionId: "gsess-1".to_string(),
+        maxPlayers: 2,
+        ..Default::default()
+    })
+    .await;
+
+    let _policy = api
+        .run_capacity_policy(CapacityPolicy::default())
+        .await
+        .expect("Cannot run the capacity policy");
+    assert_eq!(creation_policy_update(&aux_proxy, 1).await, "AcceptAll");
+    api.accept_player_session("psess-1".to_string()).await.unwrap();
+
+    // A shutdown without a termination time still keeps new players out.
+    let coordinator = ShutdownCoordinator::new(
+        api.clone(),
+        ShutdownConfig { handle_signals: false, ..Default::default() },
+    );
+    let shutdown =
+        tokio::spawn(async move { coordinator.shutdown(ShutdownReason::Requested).await });
+    assert_eq!(creation_policy_update(&aux_proxy, 3).await, "DenyAll");
+    assert!(api.is_draining());
+
+    api.remove_player_session("psess-1".to_string()).await.unwrap();
+    let exit_code = tokio::time::timeout(TIMEOUT, shutdown).await.unwrap().unwrap();
+    assert_eq!(exit_code, 0);
+    let policies: Vec<String> = aux_proxy
+        .received_messages_of_type("UpdatePlayerSessionCreationPolicy")
+        .iter()
+        .map(|message| {
+            message
+                .decode::<sdk::UpdatePlayerSessionCreationPolicy>()
+                .unwrap()
+                .newPlayerSessionCreationPolicy
+        })
+        .collect();
+    assert_eq!(policies, ["AcceptAll", "DenyAll", "DenyAll"]);
+}
+
+#[tokio::test]
+async fn process_ready_retry_test() {
+    let (aux_proxy, api) = connected_api(|config| config).await;
+
+    aux_proxy.push_response(
+        "ProcessReady",
+        MockResponse::with_status(500, r#"{"status":"ERROR_500","errorMessage":"Unavailable"}"#),
+    );
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    assert!(matches!(
+        api.process_ready(process_parameters(sender.clone())).await,
+        Err(GameLiftErrorType::InternalServiceError(_))
+    ));
+    assert_eq!(api.state(), ProcessState::Initialized);
+
+    api.process_ready(process_parameters(sender)).await.expect("ProcessReady failed");
+    assert_eq!(api.state(), ProcessState::Ready);
+    assert!(aux_proxy.wait_for_messages("ProcessReady", 2, TIMEOUT).await.is_some());
+    aux_proxy.activate_game_session(sdk::GameSession {
+        gameSessionId: "gsess-1".to_string(),
+        ..Default::default()
+    });
+    assert!(matches!(next_callback(&mut receiver).await, Callback::StartGameSession(_)));
+}
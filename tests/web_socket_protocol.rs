@@ -0,0 +1,166 @@
+use aws_gamelift_server_sdk_rs::{
+    api::Api,
+    entity::GameSession,
+    log_parameters::LogParameters,
+    notification::Notification,
+    process_parameters::ProcessParameters,
+    sdk_config::{Protocol, SdkConfig},
+    server_parameters::ServerParameters,
+};
+use futures_util::{SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::Message;
+
+const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Accepts a single connection, answers every request with success and
+/// forwards the received requests and the query string to the test.
+async fn serve(
+    listener: tokio::net::TcpListener,
+    requests: tokio::sync::mpsc::UnboundedSender<serde_json::Value>,
+    mut events: tokio::sync::mpsc::UnboundedReceiver<serde_json::Value>,
+) {
+    let (stream, _) = listener.accept().await.unwrap();
+    let mut ws_stream = tokio_tungstenite::accept_async(stream).await.unwrap();
+    loop {
+        tokio::select! {
+            Some(event) = events.recv() => {
+                ws_stream.send(Message::Text(event.to_string())).await.unwrap();
+            }
+            Some(Ok(Message::Text(text))) = ws_stream.next() => {
+                let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                ws_stream.send(success(&request)).await.unwrap();
+                let _ = requests.send(request);
+            }
+            else => return,
+        }
+    }
+}
+
+fn success(request: &serde_json::Value) -> Message {
+    let response = serde_json::json!({
+        "Action": request["Action"],
+        "RequestId": request["RequestId"],
+        "StatusCode": 200,
+    });
+    Message::Text(response.to_string())
+}
+
+/// Accepts any number of connections, counts them and answers every request
+/// with success. Closes all connections once `close` changes.
+async fn serve_until_closed(
+    listener: tokio::net::TcpListener,
+    accepted: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    close: tokio::sync::watch::Receiver<bool>,
+) {
+    while let Ok((stream, _)) = listener.accept().await {
+        accepted.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let mut close = close.clone();
+        tokio::spawn(async move {
+            let mut ws_stream = tokio_tungstenite::accept_async(stream).await.unwrap();
+            loop {
+                tokio::select! {
+                    _ = close.changed() => {
+                        let _ = ws_stream.close(None).await;
+                        return;
+                    }
+                    Some(Ok(Message::Text(text))) = ws_stream.next() => {
+                        let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                        ws_stream.send(success(&request)).await.unwrap();
+                    }
+                    else => return,
+                }
+            }
+        });
+    }
+}
+
+fn sdk_config(port: u16) -> SdkConfig {
+    SdkConfig {
+        protocol: Protocol::WebSocket(ServerParameters {
+            web_socket_url: format!("ws://127.0.0.1:{}", port),
+            process_id: "process-1".to_string(),
+            host_id: "compute-1".to_string(),
+            fleet_id: "fleet-1".to_string(),
+            auth_token: "token".to_string(),
+        }),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn web_socket_protocol_test() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (request_sender, mut requests) = tokio::sync::mpsc::unbounded_channel();
+    let (event_sender, events) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(serve(listener, request_sender, events));
+
+    let api = Api::new(sdk_config(port));
+    api.init_sdk().await.expect("Cannot initialize the SDK");
+
+    let (game_session_sender, mut game_sessions) = tokio::sync::mpsc::unbounded_channel();
+    let callback_api = api.clone();
+    api.process_ready(ProcessParameters {
+        on_start_game_session: Box::new(move |game_session: GameSession| {
+            let api = callback_api.clone();
+            let game_session_sender = game_session_sender.clone();
+            Box::pin(async move {
+                let result = api.activate_game_session().await;
+                let _ = game_session_sender.send((game_session, result));
+            })
+        }),
+        on_update_game_session: Box::new(|_| Box::pin(async {})),
+        on_process_terminate: Box::new(|| Box::pin(async {})),
+        on_health_check: Box::new(|| Box::pin(async { true })),
+        port: 14000,
+        log_parameters: LogParameters { log_paths: vec![] },
+    })
+    .await
+    .expect("ActivateServerProcess failed");
+
+    let request = tokio::time::timeout(TIMEOUT, requests.recv()).await.unwrap().unwrap();
+    assert_eq!(request["Action"], "ActivateServerProcess");
+    assert_eq!(request["Port"], 14000);
+
+    event_sender
+        .send(serde_json::json!({
+            "Action": "CreateGameSession",
+            "GameSessionId": "gsess-1",
+            "MaximumPlayerSessionCount": 4,
+        }))
+        .unwrap();
+    let (game_session, result) =
+        tokio::time::timeout(TIMEOUT, game_sessions.recv()).await.unwrap().unwrap();
+    assert_eq!(game_session.game_session_id.as_deref(), Some("gsess-1"));
+    result.expect("ActivateGameSession failed");
+    let request = loop {
+        let request = tokio::time::timeout(TIMEOUT, requests.recv()).await.unwrap().unwrap();
+        if request["Action"] != "HeartbeatServerProcess" {
+            break request;
+        }
+    };
+    assert_eq!(request["Action"], "ActivateGameSession");
+    assert_eq!(request["GameSessionId"], "gsess-1");
+}
+
+#[tokio::test]
+async fn no_reconnect_after_process_ending_test() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let accepted = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let (close_sender, close) = tokio::sync::watch::channel(false);
+    tokio::spawn(serve_until_closed(listener, accepted.clone(), close));
+
+    let api = Api::new(sdk_config(port));
+    api.init_sdk().await.expect("Cannot initialize the SDK");
+    api.process_ending().await.expect("TerminateServerProcess failed");
+
+    // GameLift drops the connection of an ending process.
+    let mut notifications = api.subscribe_notifications();
+    close_sender.send(true).unwrap();
+    assert_eq!(
+        tokio::time::timeout(TIMEOUT, notifications.recv()).await.unwrap().unwrap(),
+        Notification::ConnectionClosed
+    );
+    assert_eq!(accepted.load(std::sync::atomic::Ordering::SeqCst), 1);
+}